@@ -0,0 +1,298 @@
+use crate::NoSolutionErr;
+use std::{
+  collections::BTreeMap,
+  convert::{Infallible, TryFrom},
+  error::Error,
+  fmt,
+  str::FromStr,
+};
+
+/// The answer of one part, whatever type its solver returns.
+///
+/// Integers are compared by value, so `Integer(5)` equals `BigInteger(5)`, but text never
+/// equals an integer, see `matches` for answers read from text.
+#[derive(Debug, Clone)]
+pub enum Answer {
+  Integer(i64),
+  /// An integer that doesn't fit into an `i64`.
+  BigInteger(i128),
+  Text(String),
+  NoSolution,
+}
+
+impl Answer {
+  /// Whether this answer is `expected`, which may have been parsed from text. Such answers
+  /// don't know the type the solver returns, e.g. the labels of day 23 are text that look like
+  /// an integer, so text also matches an integer written exactly the same way.
+  pub fn matches(&self, expected: &Answer) -> bool {
+    match (self, expected) {
+      (Answer::Text(text), n) | (n, Answer::Text(text)) if n.as_i128().is_some() => {
+        *text == n.to_string()
+      }
+      _ => self == expected,
+    }
+  }
+
+  fn as_i128(&self) -> Option<i128> {
+    match *self {
+      Answer::Integer(n) => Some(n as i128),
+      Answer::BigInteger(n) => Some(n),
+      _ => None,
+    }
+  }
+}
+
+impl PartialEq for Answer {
+  fn eq(&self, other: &Answer) -> bool {
+    match (self, other) {
+      (Answer::NoSolution, Answer::NoSolution) => true,
+      (Answer::Text(a), Answer::Text(b)) => a == b,
+      _ => match (self.as_i128(), other.as_i128()) {
+        (Some(a), Some(b)) => a == b,
+        _ => false,
+      },
+    }
+  }
+}
+
+impl Eq for Answer {}
+
+impl fmt::Display for Answer {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Answer::Integer(n) => write!(f, "{}", n),
+      Answer::BigInteger(n) => write!(f, "{}", n),
+      Answer::Text(text) => write!(f, "{}", text),
+      Answer::NoSolution => write!(f, "no solution"),
+    }
+  }
+}
+
+/// Parses an answer the way it is displayed.
+impl FromStr for Answer {
+  type Err = Infallible;
+
+  fn from_str(s: &str) -> Result<Answer, Infallible> {
+    Ok(if s == "no solution" {
+      Answer::NoSolution
+    } else if let Ok(n) = s.parse::<i128>() {
+      Answer::from(n)
+    } else {
+      Answer::Text(s.to_string())
+    })
+  }
+}
+
+macro_rules! from_small_integer {
+  ($($t:ty),*) => {$(
+    impl From<$t> for Answer {
+      fn from(n: $t) -> Answer {
+        Answer::Integer(n as i64)
+      }
+    }
+  )*};
+}
+
+macro_rules! from_large_integer {
+  ($($t:ty),*) => {$(
+    impl From<$t> for Answer {
+      fn from(n: $t) -> Answer {
+        match i64::try_from(n) {
+          Ok(n) => Answer::Integer(n),
+          Err(_) => Answer::BigInteger(n as i128),
+        }
+      }
+    }
+  )*};
+}
+
+from_small_integer!(u8, u16, u32, i8, i16, i32, i64);
+from_large_integer!(u64, usize, i128);
+
+impl From<String> for Answer {
+  fn from(text: String) -> Answer {
+    Answer::Text(text)
+  }
+}
+
+impl From<&str> for Answer {
+  fn from(text: &str) -> Answer {
+    Answer::Text(text.to_string())
+  }
+}
+
+impl<T: Into<Answer>> From<Option<T>> for Answer {
+  fn from(answer: Option<T>) -> Answer {
+    answer.map_or(Answer::NoSolution, Into::into)
+  }
+}
+
+impl<T: Into<Answer>> From<Result<T, NoSolutionErr>> for Answer {
+  fn from(answer: Result<T, NoSolutionErr>) -> Answer {
+    answer.ok().into()
+  }
+}
+
+/// The expected answers of puzzle parts, keyed by day and part.
+///
+/// The text format has one `<day> <part> <answer>` entry per line, blank lines
+/// and lines starting with `#` are ignored.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KnownAnswers {
+  answers: BTreeMap<(u8, u8), Answer>,
+}
+
+impl KnownAnswers {
+  pub fn get(&self, day: u8, part: u8) -> Option<&Answer> {
+    self.answers.get(&(day, part))
+  }
+
+  pub fn insert(&mut self, day: u8, part: u8, answer: Answer) {
+    self.answers.insert((day, part), answer);
+  }
+
+  pub fn len(&self) -> usize {
+    self.answers.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.answers.is_empty()
+  }
+}
+
+impl fmt::Display for KnownAnswers {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    for ((day, part), answer) in &self.answers {
+      writeln!(f, "{} {} {}", day, part, answer)?;
+    }
+    Ok(())
+  }
+}
+
+/// An invalid line in a known answers file, `line` is 1-based.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KnownAnswersError {
+  pub line: usize,
+  pub reason: String,
+}
+
+impl fmt::Display for KnownAnswersError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "line {}: {}", self.line, self.reason)
+  }
+}
+
+impl Error for KnownAnswersError {}
+
+impl FromStr for KnownAnswers {
+  type Err = KnownAnswersError;
+
+  fn from_str(s: &str) -> Result<KnownAnswers, KnownAnswersError> {
+    let mut known = KnownAnswers::default();
+
+    for (i, line) in s.lines().enumerate() {
+      let line = line.trim();
+      if line.is_empty() || line.starts_with('#') {
+        continue;
+      }
+      let error = |reason: &str| KnownAnswersError {
+        line: i + 1,
+        reason: format!("{}: `{}`", reason, line),
+      };
+
+      let mut fields = line.splitn(3, ' ');
+      let day = fields
+        .next()
+        .and_then(|day| day.parse().ok())
+        .filter(|day| (1..=25).contains(day))
+        .ok_or_else(|| error("invalid day"))?;
+      let part = fields
+        .next()
+        .and_then(|part| part.parse().ok())
+        .filter(|part| (1..=2).contains(part))
+        .ok_or_else(|| error("invalid part"))?;
+      let answer = fields
+        .next()
+        .map(str::trim)
+        .filter(|answer| !answer.is_empty())
+        .ok_or_else(|| error("missing answer"))?;
+
+      if known.get(day, part).is_some() {
+        return Err(error("duplicate entry"));
+      }
+      known.insert(day, part, answer.parse().unwrap());
+    }
+
+    Ok(known)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_answer_equality() {
+    assert_eq!(Answer::from(42u16), Answer::from(42usize));
+    assert_eq!(Answer::BigInteger(42), Answer::Integer(42));
+    assert_eq!(Answer::from(None::<i64>), Answer::NoSolution);
+    assert_eq!(
+      Answer::from(Err::<u64, _>(NoSolutionErr {})),
+      Answer::NoSolution
+    );
+    assert_ne!(Answer::from("42"), Answer::from(42));
+    assert_ne!(Answer::from("no solution"), Answer::NoSolution);
+    assert_ne!(Answer::NoSolution, Answer::Text(String::new()));
+  }
+
+  #[test]
+  fn test_answer_matches() {
+    assert!(Answer::from("94238657").matches(&"94238657".parse().unwrap()));
+    assert!(Answer::from(42).matches(&Answer::from("42")));
+    assert!(!Answer::from("042").matches(&Answer::from(42)));
+    assert!(!Answer::from(42).matches(&Answer::from(43)));
+    assert!(Answer::BigInteger(42).matches(&Answer::Integer(42)));
+    assert!(!Answer::from("no solution").matches(&Answer::NoSolution));
+    assert!(!Answer::NoSolution.matches(&Answer::from("no solution")));
+    assert!(Answer::NoSolution.matches(&Answer::NoSolution));
+    assert_ne!(Answer::from(u64::MAX), Answer::from(-1));
+  }
+
+  #[test]
+  fn test_answer_round_trip() {
+    for answer in [
+      Answer::from(-3),
+      Answer::from(672754131923874i128),
+      Answer::from(u64::MAX),
+      Answer::from("hkflr,ctmcqjf"),
+      Answer::NoSolution,
+    ] {
+      assert_eq!(answer.to_string().parse::<Answer>().unwrap(), answer);
+    }
+    assert!(matches!(Answer::from(u64::MAX), Answer::BigInteger(_)));
+  }
+
+  #[test]
+  fn test_known_answers() {
+    let known: KnownAnswers = "# day part answer\n1 1 514579\n\n21 2 mxmxvkd,sqjhc,fvjkl\n"
+      .parse()
+      .unwrap();
+    assert_eq!(known.len(), 2);
+    assert_eq!(known.get(1, 1), Some(&Answer::from(514579)));
+    assert_eq!(known.get(21, 2), Some(&Answer::from("mxmxvkd,sqjhc,fvjkl")));
+    assert_eq!(known.get(1, 2), None);
+    assert_eq!(known.to_string().parse::<KnownAnswers>().unwrap(), known);
+
+    let error = "1 1 514579\n26 1 5".parse::<KnownAnswers>().unwrap_err();
+    assert_eq!(error.line, 2);
+    assert!("1 1 514579\n1 1 514579".parse::<KnownAnswers>().is_err());
+    assert!("1 3 514579".parse::<KnownAnswers>().is_err());
+    assert!("1 1".parse::<KnownAnswers>().is_err());
+  }
+
+  #[test]
+  fn test_known_answers_file() {
+    let known: KnownAnswers = include_str!("../input/2020/answers.txt").parse().unwrap();
+    assert_eq!(known.len(), 49);
+  }
+}
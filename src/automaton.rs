@@ -0,0 +1,416 @@
+//! A cellular automaton, in which cells are alive or dead and change according to the number of
+//! their living neighbours. Day 11, 17 and 24 are automata with different topologies and rules.
+
+use crate::grid::{Grid, DIRECTIONS_8};
+use itertools::Either;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::hash::Hash;
+use std::str::FromStr;
+
+/// The cells of an automaton and which cells are neighbours.
+pub trait Topology {
+  type Cell: Clone + Eq + Hash;
+
+  /// Returns all cells if there are finitely many of them, then the automaton keeps one flag per
+  /// cell and asks for the neighbours with `neighbour_indices`.
+  /// Otherwise only dead cells next to a living one are checked for a birth.
+  fn cells(&self) -> Option<&[Self::Cell]> {
+    None
+  }
+
+  /// The positions in `cells` of the neighbours of the cell at position `index`, only called if
+  /// there are finitely many cells.
+  fn neighbour_indices(&self, _index: usize) -> &[usize] {
+    &[]
+  }
+
+  fn neighbours(&self, cell: &Self::Cell) -> impl Iterator<Item = Self::Cell> + '_;
+}
+
+/// The cells of a `Grid` for which `is_cell` holds, neighbours are the first cells seen in the
+/// 8 directions within `max_sight` steps.
+pub struct SquareGrid {
+  cells: Vec<(usize, usize)>,
+  /// the position of each cell in `cells`
+  index: Grid<Option<usize>>,
+  neighbours: Vec<Vec<usize>>,
+}
+
+impl SquareGrid {
+  pub fn new<T, F: Fn(&T) -> bool>(grid: &Grid<T>, max_sight: usize, is_cell: F) -> SquareGrid {
+    let cells = grid
+      .positions()
+      .filter(|&position| is_cell(&grid[position]))
+      .collect::<Vec<_>>();
+    let mut index = Grid::filled(grid.width(), grid.height(), None);
+    for (i, &cell) in cells.iter().enumerate() {
+      index[cell] = Some(i);
+    }
+    let neighbours = cells
+      .iter()
+      .map(|&(x, y)| {
+        DIRECTIONS_8
+          .iter()
+          .filter_map(|&direction| {
+            grid
+              .ray(x, y, direction)
+              .take(max_sight)
+              .find_map(|position| index[position])
+          })
+          .collect()
+      })
+      .collect();
+    SquareGrid {
+      cells,
+      index,
+      neighbours,
+    }
+  }
+}
+
+impl Topology for SquareGrid {
+  type Cell = (usize, usize);
+
+  fn cells(&self) -> Option<&[(usize, usize)]> {
+    Some(&self.cells)
+  }
+
+  fn neighbour_indices(&self, index: usize) -> &[usize] {
+    &self.neighbours[index]
+  }
+
+  fn neighbours(&self, &cell: &(usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+    let neighbours = match self.index.get(cell.0, cell.1).copied().flatten() {
+      Some(i) => &self.neighbours[i][..],
+      None => &[],
+    };
+    neighbours.iter().map(move |&i| self.cells[i])
+  }
+}
+
+/// The infinite integer lattice of `n` dimensions, every cell has `3^n - 1` neighbours.
+pub struct Lattice {
+  offsets: Vec<Vec<i32>>,
+}
+
+impl Lattice {
+  pub fn new(n: usize) -> Lattice {
+    let offsets = (0..3usize.pow(n as u32))
+      .map(|i| {
+        let mut x = i;
+        let mut offset = Vec::with_capacity(n);
+        for _j in 0..n {
+          offset.push((x % 3) as i32 - 1);
+          x /= 3;
+        }
+        offset
+      })
+      .filter(|offset| offset.iter().any(|&o| o != 0))
+      .collect();
+    Lattice { offsets }
+  }
+}
+
+impl Topology for Lattice {
+  type Cell = Vec<i32>;
+
+  fn neighbours(&self, cell: &Vec<i32>) -> impl Iterator<Item = Vec<i32>> + '_ {
+    let cell = cell.clone();
+    self
+      .offsets
+      .iter()
+      .map(move |offset| cell.iter().zip(offset).map(|(p, o)| p + o).collect())
+  }
+}
+
+/// An infinite hexagonal grid in axial coordinates, every cell has 6 neighbours.
+pub struct Hex;
+
+impl Topology for Hex {
+  type Cell = (i32, i32);
+
+  fn neighbours(&self, &(r, q): &(i32, i32)) -> impl Iterator<Item = (i32, i32)> + '_ {
+    [(1, 0), (-1, 0), (0, 1), (0, -1), (1, -1), (-1, 1)]
+      .iter()
+      .map(move |(dr, dq)| (r + dr, q + dq))
+  }
+}
+
+/// The numbers of living neighbours for which a dead cell comes alive and a living cell survives.
+/// Written like `B3/S23`, which only allows neighbour counts up to 9.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+  birth: Vec<usize>,
+  survival: Vec<usize>,
+}
+
+impl Rule {
+  pub fn new(birth: &[usize], survival: &[usize]) -> Rule {
+    Rule {
+      birth: birth.to_vec(),
+      survival: survival.to_vec(),
+    }
+  }
+
+  pub fn is_alive(&self, alive: bool, neighbours: usize) -> bool {
+    if alive {
+      self.survival.contains(&neighbours)
+    } else {
+      self.birth.contains(&neighbours)
+    }
+  }
+}
+
+impl fmt::Display for Rule {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let counts = |counts: &[usize]| counts.iter().map(usize::to_string).collect::<String>();
+    write!(f, "B{}/S{}", counts(&self.birth), counts(&self.survival))
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidRule(String);
+
+impl fmt::Display for InvalidRule {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(
+      f,
+      "invalid rule `{}`, expected something like `B3/S23`",
+      self.0
+    )
+  }
+}
+
+impl Error for InvalidRule {}
+
+impl FromStr for Rule {
+  type Err = InvalidRule;
+
+  fn from_str(s: &str) -> Result<Rule, InvalidRule> {
+    let counts = |counts: &str| {
+      counts
+        .chars()
+        .map(|c| c.to_digit(10).map(|d| d as usize))
+        .collect::<Option<Vec<_>>>()
+    };
+    s.split_once('/')
+      .and_then(|(birth, survival)| {
+        let birth = counts(birth.strip_prefix('B')?)?;
+        let survival = counts(survival.strip_prefix('S')?)?;
+        Some(Rule { birth, survival })
+      })
+      .ok_or_else(|| InvalidRule(s.to_string()))
+  }
+}
+
+/// The living cells of a `Topology` evolving under a `Rule`.
+///
+/// As an iterator it steps the automaton and yields the number of living cells after each step,
+/// until a step doesn't change anything anymore.
+pub struct Automaton<T: Topology> {
+  topology: T,
+  rule: Rule,
+  state: State<T::Cell>,
+}
+
+enum State<C> {
+  /// The living cells of an infinite topology.
+  Sparse(HashSet<C>),
+  /// Whether each of the finitely many cells is alive, in the order of `Topology::cells`.
+  Dense { alive: Vec<bool>, count: usize },
+}
+
+impl<T: Topology> Automaton<T> {
+  /// Living cells that aren't cells of a finite topology are ignored.
+  pub fn new<I: IntoIterator<Item = T::Cell>>(topology: T, rule: Rule, alive: I) -> Automaton<T> {
+    let state = match topology.cells() {
+      Some(cells) => {
+        let index = cells
+          .iter()
+          .enumerate()
+          .map(|(i, cell)| (cell, i))
+          .collect::<HashMap<_, _>>();
+        let mut flags = vec![false; cells.len()];
+        for cell in alive {
+          if let Some(&i) = index.get(&cell) {
+            flags[i] = true;
+          }
+        }
+        let count = flags.iter().filter(|&&alive| alive).count();
+        State::Dense {
+          alive: flags,
+          count,
+        }
+      }
+      None => State::Sparse(alive.into_iter().collect()),
+    };
+    Automaton {
+      topology,
+      rule,
+      state,
+    }
+  }
+
+  /// The living cells, in no particular order.
+  pub fn alive(&self) -> impl Iterator<Item = &T::Cell> + '_ {
+    match &self.state {
+      State::Sparse(alive) => Either::Left(alive.iter()),
+      State::Dense { alive, .. } => Either::Right(
+        self
+          .topology
+          .cells()
+          .unwrap_or(&[])
+          .iter()
+          .zip(alive)
+          .filter(|&(_, &alive)| alive)
+          .map(|(cell, _)| cell),
+      ),
+    }
+  }
+
+  pub fn alive_count(&self) -> usize {
+    match &self.state {
+      State::Sparse(alive) => alive.len(),
+      State::Dense { count, .. } => *count,
+    }
+  }
+
+  /// Advances all cells by one generation, returns whether any cell changed.
+  pub fn step(&mut self) -> bool {
+    let (topology, rule) = (&self.topology, &self.rule);
+    match &mut self.state {
+      State::Sparse(alive) => step_sparse(topology, rule, alive),
+      State::Dense { alive, count } => {
+        let next_alive = (0..alive.len())
+          .map(|i| {
+            let neighbours = topology
+              .neighbour_indices(i)
+              .iter()
+              .filter(|&&j| alive[j])
+              .count();
+            rule.is_alive(alive[i], neighbours)
+          })
+          .collect::<Vec<_>>();
+        let changed = next_alive != *alive;
+        *count = next_alive.iter().filter(|&&alive| alive).count();
+        *alive = next_alive;
+        changed
+      }
+    }
+  }
+
+  /// Steps until the cells don't change anymore and returns the number of steps that changed
+  /// something. Never returns if the automaton doesn't reach a stable state.
+  pub fn run_until_stable(&mut self) -> usize {
+    self.by_ref().count()
+  }
+}
+
+/// Advances the living cells of an infinite topology, returns whether any cell changed.
+fn step_sparse<T: Topology>(topology: &T, rule: &Rule, alive: &mut HashSet<T::Cell>) -> bool {
+  let mut neighbour_count: HashMap<T::Cell, usize> = HashMap::new();
+  for cell in alive.iter() {
+    for neighbour in topology.neighbours(cell) {
+      *neighbour_count.entry(neighbour).or_insert(0) += 1;
+    }
+  }
+
+  // living cells without any living neighbours aren't counted
+  let lonely_survivors = if rule.is_alive(true, 0) {
+    alive
+      .iter()
+      .filter(|&cell| !neighbour_count.contains_key(cell))
+      .cloned()
+      .collect()
+  } else {
+    Vec::new()
+  };
+  let next_alive = neighbour_count
+    .into_iter()
+    .filter(|(cell, count)| rule.is_alive(alive.contains(cell), *count))
+    .map(|(cell, _)| cell)
+    .chain(lonely_survivors)
+    .collect::<HashSet<_>>();
+
+  let changed = next_alive != *alive;
+  *alive = next_alive;
+  changed
+}
+
+impl<T: Topology> Iterator for Automaton<T> {
+  type Item = usize;
+
+  fn next(&mut self) -> Option<usize> {
+    if self.step() {
+      Some(self.alive_count())
+    } else {
+      None
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::{Automaton, Hex, Lattice, Rule, SquareGrid, Topology};
+  use crate::grid::Grid;
+  use std::collections::HashSet;
+
+  #[test]
+  fn test_rule() {
+    let rule: Rule = "B3/S23".parse().unwrap();
+    assert_eq!(rule, Rule::new(&[3], &[2, 3]));
+    assert_eq!(rule.to_string(), "B3/S23");
+    assert!(rule.is_alive(false, 3));
+    assert!(!rule.is_alive(true, 4));
+    assert_eq!("B/S".parse::<Rule>().unwrap(), Rule::new(&[], &[]));
+    assert!("B3S23".parse::<Rule>().is_err());
+    assert!("B3/S2x".parse::<Rule>().is_err());
+  }
+
+  #[test]
+  fn test_lattice_blinker() {
+    let blinker = vec![vec![0, -1], vec![0, 0], vec![0, 1]];
+    let mut automaton = Automaton::new(Lattice::new(2), "B3/S23".parse().unwrap(), blinker.clone());
+    assert_eq!(automaton.by_ref().take(2).collect::<Vec<_>>(), vec![3, 3]);
+    assert_eq!(
+      automaton.alive().cloned().collect::<HashSet<_>>(),
+      blinker.into_iter().collect()
+    );
+  }
+
+  #[test]
+  fn test_stable_states() {
+    let block = vec![vec![0, 0], vec![0, 1], vec![1, 0], vec![1, 1]];
+    let mut automaton = Automaton::new(Lattice::new(2), "B3/S23".parse().unwrap(), block);
+    assert_eq!(automaton.run_until_stable(), 0);
+
+    // every cell is born, then all with 3 or more neighbours die
+    let grid = Grid::filled(3, 3, ());
+    let topology = SquareGrid::new(&grid, 1, |_| true);
+    assert_eq!(topology.neighbours(&(1, 1)).count(), 8);
+    assert_eq!(
+      topology.neighbours(&(0, 0)).collect::<HashSet<_>>(),
+      vec![(1, 0), (0, 1), (1, 1)].into_iter().collect()
+    );
+    assert_eq!(topology.neighbours(&(5, 5)).count(), 0);
+    let mut automaton = Automaton::new(
+      SquareGrid::new(&grid, 1, |_| true),
+      Rule::new(&[0], &[0, 1, 2]),
+      vec![],
+    );
+    assert_eq!(automaton.next(), Some(9));
+    assert_eq!(automaton.next(), Some(0));
+    assert_eq!(automaton.next(), Some(9));
+    assert_eq!(automaton.alive().count(), 9);
+
+    let mut automaton = Automaton::new(Lattice::new(2), Rule::new(&[], &[0]), vec![vec![0, 0]]);
+    assert_eq!(automaton.run_until_stable(), 0);
+    assert_eq!(automaton.alive_count(), 1);
+
+    let mut automaton = Automaton::new(Hex, "B2/S12".parse().unwrap(), vec![(0, 0)]);
+    assert_eq!(automaton.run_until_stable(), 1);
+    assert_eq!(automaton.alive_count(), 0);
+  }
+}
@@ -0,0 +1,543 @@
+extern crate advent_of_code_2020;
+extern crate serde;
+extern crate serde_json;
+
+use advent_of_code_2020::answer::{Answer, KnownAnswers};
+use advent_of_code_2020::diagnostics::{Diagnostics, Logger, Silent};
+use advent_of_code_2020::*;
+use serde::Serialize;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::process;
+use std::time::{Duration, Instant};
+
+const USAGE: &str = "Usage:
+  aoc2020 run --day <day> [--part <part>] [--input <path>] [--verbose]
+  aoc2020 run --all [--inputs <dir>] [--verbose]
+  aoc2020 verify [--day <day>] [--inputs <dir>] [--answers <path>]
+  aoc2020 bench [--day <day>] [--inputs <dir>] [--iterations <n>] [--json <path>]
+
+Without --input the puzzle input is read from <dir>/day<day>.txt,
+where <dir> defaults to input/2020. With --verbose the diagnostics
+of the solvers are printed to stderr.
+
+verify compares the answers of all days, or only of --day, with the
+known answers in <dir>/answers.txt.
+
+bench runs the generator and each part of all days, or only of --day,
+<n> times (default 5) and prints the min, median and max durations.
+With --json the timings are also written to <path> as JSON.";
+
+const DEFAULT_INPUT_DIR: &str = "input/2020";
+const DEFAULT_ITERATIONS: usize = 5;
+
+enum Command {
+  Run,
+  Verify,
+  Bench,
+}
+
+struct Options {
+  command: Command,
+  days: Vec<u8>,
+  part: Option<u8>,
+  input: Option<String>,
+  input_dir: String,
+  answers: Option<String>,
+  iterations: usize,
+  json: Option<String>,
+  verbose: bool,
+}
+
+struct PartResult {
+  day: u8,
+  part: u8,
+  answer: Result<Answer, String>,
+  generator_time: Duration,
+  runner_time: Duration,
+}
+
+/// Runs `f`, turning a panic into an error so that one broken solver doesn't abort the other days.
+fn catch_panic<T, F>(f: F) -> Result<T, Box<dyn Error>>
+where
+  F: FnOnce() -> Result<T, Box<dyn Error>>,
+{
+  panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+    let message = payload
+      .downcast_ref::<&str>()
+      .map(|s| s.to_string())
+      .or_else(|| payload.downcast_ref::<String>().cloned())
+      .unwrap_or_else(|| String::from("unknown panic"));
+    Err(format!("panicked: {}", message).into())
+  })
+}
+
+fn run_part(
+  day: u8,
+  part: u8,
+  input: &str,
+  diagnostics: &dyn Diagnostics,
+) -> Result<(Answer, Duration, Duration), Box<dyn Error>> {
+  let solution = solution(day).ok_or_else(|| format!("there is no day {}", day))?;
+  let start = Instant::now();
+  let parsed = catch_panic(|| Ok(solution.parse(input)?))?;
+  let generated = Instant::now();
+  let answer = catch_panic(|| solution.solve(part, &*parsed, diagnostics))?;
+  Ok((answer, generated - start, generated.elapsed()))
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+  let mut args = args.iter();
+  let command = match args.next().map(String::as_str) {
+    Some("run") => Command::Run,
+    Some("verify") => Command::Verify,
+    Some("bench") => Command::Bench,
+    Some(command) => return Err(format!("unknown command `{}`", command)),
+    None => return Err(String::from("missing command")),
+  };
+
+  let mut day = None;
+  let mut all = false;
+  let mut part = None;
+  let mut input = None;
+  let mut input_dir = String::from(DEFAULT_INPUT_DIR);
+  let mut answers = None;
+  let mut iterations = DEFAULT_ITERATIONS;
+  let mut json = None;
+  let mut verbose = false;
+
+  while let Some(arg) = args.next() {
+    let mut value = || {
+      args
+        .next()
+        .cloned()
+        .ok_or_else(|| format!("missing value for `{}`", arg))
+    };
+    match arg.as_str() {
+      "--day" => day = Some(parse_number(&value()?, 1..=25, "day")?),
+      "--part" => part = Some(parse_number(&value()?, 1..=2, "part")?),
+      "--input" => input = Some(value()?),
+      "--inputs" => input_dir = value()?,
+      "--answers" => answers = Some(value()?),
+      "--iterations" => {
+        let value = value()?;
+        iterations = value
+          .parse()
+          .ok()
+          .filter(|&n| n > 0)
+          .ok_or_else(|| format!("iterations has to be a positive number, got `{}`", value))?
+      }
+      "--json" => json = Some(value()?),
+      "--all" => all = true,
+      "--verbose" => verbose = true,
+      _ => return Err(format!("unknown argument `{}`", arg)),
+    }
+  }
+
+  let days = match (&command, day, all) {
+    (_, Some(day), false) => vec![day],
+    (Command::Verify, None, _) | (Command::Bench, None, _) | (Command::Run, None, true) => {
+      (1..=25).collect()
+    }
+    _ => return Err(String::from("either --day or --all is required")),
+  };
+
+  if all && (part.is_some() || input.is_some()) {
+    return Err(String::from(
+      "--part and --input can only be used with --day",
+    ));
+  }
+  if let Command::Verify | Command::Bench = command {
+    if part.is_some() || input.is_some() {
+      return Err(String::from("--part and --input can only be used with run"));
+    }
+  }
+
+  Ok(Options {
+    command,
+    days,
+    part,
+    input,
+    input_dir,
+    answers,
+    iterations,
+    json,
+    verbose,
+  })
+}
+
+fn parse_number(
+  value: &str,
+  range: std::ops::RangeInclusive<u8>,
+  name: &str,
+) -> Result<u8, String> {
+  value
+    .parse()
+    .ok()
+    .filter(|n| range.contains(n))
+    .ok_or_else(|| {
+      format!(
+        "{} has to be between {} and {}, got `{}`",
+        name,
+        range.start(),
+        range.end(),
+        value
+      )
+    })
+}
+
+fn read_input(options: &Options, day: u8) -> Result<String, String> {
+  let path = options
+    .input
+    .clone()
+    .unwrap_or_else(|| format!("{}/day{}.txt", options.input_dir, day));
+  let input = fs::read_to_string(&path).map_err(|e| format!("failed to read `{}`: {}", path, e))?;
+  // the generators expect the input without the trailing newline, like cargo-aoc passes it
+  Ok(input.trim_end_matches('\n').to_string())
+}
+
+fn run(options: &Options) -> Vec<PartResult> {
+  let mut results = Vec::new();
+  let diagnostics: &dyn Diagnostics = if options.verbose { &Logger } else { &Silent };
+
+  for &day in &options.days {
+    let parts = match options.part {
+      Some(part) => vec![part],
+      None => solution(day)
+        .map(|s| s.parts().to_vec())
+        .unwrap_or_default(),
+    };
+
+    let input = read_input(options, day);
+
+    for part in parts {
+      let result = match &input {
+        Ok(input) => run_part(day, part, input, diagnostics).map_err(|e| e.to_string()),
+        Err(e) => Err(e.clone()),
+      };
+      results.push(match result {
+        Ok((answer, generator_time, runner_time)) => PartResult {
+          day,
+          part,
+          answer: Ok(answer),
+          generator_time,
+          runner_time,
+        },
+        Err(e) => PartResult {
+          day,
+          part,
+          answer: Err(e),
+          generator_time: Duration::default(),
+          runner_time: Duration::default(),
+        },
+      });
+    }
+  }
+
+  results
+}
+
+fn format_duration(duration: Duration) -> String {
+  format!("{:.3} ms", duration.as_secs_f64() * 1000.0)
+}
+
+fn answer_cell(result: &PartResult) -> String {
+  match &result.answer {
+    Ok(answer) => answer.to_string(),
+    Err(e) => format!("FAILED: {}", e),
+  }
+}
+
+fn print_results(results: &[PartResult]) {
+  let rows: Vec<Vec<String>> = results
+    .iter()
+    .map(|r| {
+      vec![
+        r.day.to_string(),
+        r.part.to_string(),
+        answer_cell(r),
+        format_duration(r.generator_time),
+        format_duration(r.runner_time),
+      ]
+    })
+    .collect();
+  print_table(&["Day", "Part", "Answer", "Generator", "Runner"], &rows);
+}
+
+/// Prints the comparison of `results` with the known answers and returns whether all of them match.
+fn print_verification(results: &[PartResult], known: &KnownAnswers) -> bool {
+  let (mut ok, mut mismatched, mut missing, mut failed) = (0, 0, 0, 0);
+
+  let rows: Vec<Vec<String>> = results
+    .iter()
+    .map(|r| {
+      let expected = known.get(r.day, r.part);
+      let status = match (&r.answer, expected) {
+        (Err(_), _) => {
+          failed += 1;
+          "FAILED"
+        }
+        (Ok(_), None) => {
+          missing += 1;
+          "missing"
+        }
+        (Ok(answer), Some(expected)) if answer.matches(expected) => {
+          ok += 1;
+          "ok"
+        }
+        (Ok(_), Some(_)) => {
+          mismatched += 1;
+          "MISMATCH"
+        }
+      };
+      vec![
+        r.day.to_string(),
+        r.part.to_string(),
+        status.to_string(),
+        answer_cell(r),
+        expected.map(Answer::to_string).unwrap_or_default(),
+        format_duration(r.generator_time),
+        format_duration(r.runner_time),
+      ]
+    })
+    .collect();
+
+  print_table(
+    &[
+      "Day",
+      "Part",
+      "Status",
+      "Answer",
+      "Expected",
+      "Generator",
+      "Runner",
+    ],
+    &rows,
+  );
+  println!(
+    "\n{} ok, {} mismatched, {} missing, {} failed",
+    ok, mismatched, missing, failed
+  );
+
+  mismatched == 0 && failed == 0
+}
+
+fn print_table(header: &[&str], rows: &[Vec<String>]) {
+  let mut widths: Vec<usize> = header.iter().map(|h| h.len()).collect();
+  for row in rows {
+    for (width, cell) in widths.iter_mut().zip(row.iter()) {
+      *width = usize::max(*width, cell.chars().count());
+    }
+  }
+
+  let print_row = |cells: &[&str]| {
+    let line = cells
+      .iter()
+      .zip(widths.iter())
+      .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+      .collect::<Vec<_>>()
+      .join(" | ");
+    println!("{}", line.trim_end());
+  };
+
+  print_row(header);
+  println!(
+    "{}",
+    widths
+      .iter()
+      .map(|w| "-".repeat(*w))
+      .collect::<Vec<_>>()
+      .join("-+-")
+  );
+  for row in rows {
+    print_row(&row.iter().map(String::as_str).collect::<Vec<_>>());
+  }
+}
+
+fn read_known_answers(options: &Options) -> Result<KnownAnswers, String> {
+  let path = options
+    .answers
+    .clone()
+    .unwrap_or_else(|| format!("{}/answers.txt", options.input_dir));
+  fs::read_to_string(&path)
+    .map_err(|e| format!("failed to read `{}`: {}", path, e))?
+    .parse()
+    .map_err(|e| format!("invalid known answers in `{}`, {}", path, e))
+}
+
+/// The min, median and max of repeated measurements, in nanoseconds.
+#[derive(Serialize)]
+struct Timing {
+  min_ns: u64,
+  median_ns: u64,
+  max_ns: u64,
+}
+
+impl Timing {
+  fn new(mut durations: Vec<Duration>) -> Timing {
+    durations.sort();
+    let ns = |d: Duration| d.as_nanos() as u64;
+    Timing {
+      min_ns: ns(durations[0]),
+      median_ns: ns(durations[durations.len() / 2]),
+      max_ns: ns(durations[durations.len() - 1]),
+    }
+  }
+}
+
+#[derive(Serialize)]
+struct PartBench {
+  part: u8,
+  answer: String,
+  timing: Timing,
+}
+
+#[derive(Serialize)]
+struct DayBench {
+  day: u8,
+  generator: Timing,
+  parts: Vec<PartBench>,
+}
+
+#[derive(Serialize)]
+struct BenchReport {
+  iterations: usize,
+  days: Vec<DayBench>,
+}
+
+fn bench_day(day: u8, input: &str, iterations: usize) -> Result<DayBench, Box<dyn Error>> {
+  let solution = solution(day).ok_or_else(|| format!("there is no day {}", day))?;
+  let parts = solution.parts();
+  let mut generator_times = Vec::with_capacity(iterations);
+  let mut part_times = vec![Vec::with_capacity(iterations); parts.len()];
+  let mut answers = vec![String::new(); parts.len()];
+
+  for _ in 0..iterations {
+    let start = Instant::now();
+    let parsed = catch_panic(|| Ok(solution.parse(input)?))?;
+    generator_times.push(start.elapsed());
+
+    for (i, &part) in parts.iter().enumerate() {
+      let start = Instant::now();
+      let answer = catch_panic(|| solution.solve(part, &*parsed, &Silent))?;
+      part_times[i].push(start.elapsed());
+      answers[i] = answer.to_string();
+    }
+  }
+
+  Ok(DayBench {
+    day,
+    generator: Timing::new(generator_times),
+    parts: parts
+      .iter()
+      .zip(part_times)
+      .zip(answers)
+      .map(|((&part, times), answer)| PartBench {
+        part,
+        answer,
+        timing: Timing::new(times),
+      })
+      .collect(),
+  })
+}
+
+/// Benchmarks the days in `options` and prints a table of the timings.
+/// Returns the report and whether all days could be benchmarked.
+fn bench(options: &Options) -> (BenchReport, bool) {
+  let mut report = BenchReport {
+    iterations: options.iterations,
+    days: Vec::new(),
+  };
+  let mut success = true;
+
+  let mut rows = Vec::new();
+  for &day in &options.days {
+    let result = read_input(options, day)
+      .map_err(Box::<dyn Error>::from)
+      .and_then(|input| bench_day(day, &input, options.iterations));
+
+    match result {
+      Ok(day_bench) => {
+        let timing_row = |step: String, timing: &Timing| {
+          vec![
+            day.to_string(),
+            step,
+            format_duration(Duration::from_nanos(timing.min_ns)),
+            format_duration(Duration::from_nanos(timing.median_ns)),
+            format_duration(Duration::from_nanos(timing.max_ns)),
+          ]
+        };
+        rows.push(timing_row(String::from("generator"), &day_bench.generator));
+        for part in &day_bench.parts {
+          rows.push(timing_row(format!("part {}", part.part), &part.timing));
+        }
+        report.days.push(day_bench);
+      }
+      Err(e) => {
+        eprintln!("day {}: {}", day, e);
+        rows.push(vec![
+          day.to_string(),
+          String::from("FAILED"),
+          String::new(),
+          String::new(),
+          String::new(),
+        ]);
+        success = false;
+      }
+    }
+  }
+
+  print_table(&["Day", "Step", "Min", "Median", "Max"], &rows);
+  (report, success)
+}
+
+fn write_json_report(path: &str, report: &BenchReport) -> Result<(), String> {
+  let json = serde_json::to_string_pretty(report).unwrap();
+  fs::write(path, json + "\n").map_err(|e| format!("failed to write `{}`: {}", path, e))
+}
+
+fn main() {
+  let args: Vec<String> = env::args().skip(1).collect();
+  let options = match parse_args(&args) {
+    Ok(options) => options,
+    Err(e) => {
+      eprintln!("error: {}\n\n{}", e, USAGE);
+      process::exit(2);
+    }
+  };
+
+  let success = match options.command {
+    Command::Run => {
+      let results = run(&options);
+      print_results(&results);
+      let failed = |r: &PartResult| r.answer.as_ref().map_or(true, |a| *a == Answer::NoSolution);
+      !results.iter().any(failed)
+    }
+    Command::Verify => {
+      let known = match read_known_answers(&options) {
+        Ok(known) => known,
+        Err(e) => {
+          eprintln!("error: {}", e);
+          process::exit(2);
+        }
+      };
+      print_verification(&run(&options), &known)
+    }
+    Command::Bench => {
+      let (report, success) = bench(&options);
+      if let Some(path) = &options.json {
+        if let Err(e) = write_json_report(path, &report) {
+          eprintln!("error: {}", e);
+          process::exit(2);
+        }
+      }
+      success
+    }
+  };
+
+  if !success {
+    process::exit(1);
+  }
+}
@@ -1,40 +1,461 @@
-use std::collections::HashSet;
+use crate::{
+  answer::Answer, diagnostics::Diagnostics, input::lines, parse_at, solution::Solution, ParseError,
+};
+use itertools::Either;
+use std::{error::Error, fmt};
 
+const DAY: u8 = 1;
 const GOAL: i64 = 2020;
 
-#[aoc_generator(day1)]
-pub fn input_generator_day1(input: &str) -> HashSet<i64> {
-  input.lines().map(|l| l.parse::<i64>().unwrap()).collect()
+/// The entries of an expense report, which may contain the same amount several times.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpenseReport {
+  /// sorted, so duplicates are next to each other
+  entries: Vec<i64>,
 }
 
-#[aoc(day1, part1)]
-pub fn solve_part1(input: &HashSet<i64>) -> Option<i64> {
-  sum2(&input, GOAL).map(|(a, b)| a * b)
+impl ExpenseReport {
+  pub fn new(mut entries: Vec<i64>) -> ExpenseReport {
+    entries.sort_unstable();
+    ExpenseReport { entries }
+  }
+
+  pub fn entries(&self) -> &[i64] {
+    &self.entries
+  }
+
+  /// Returns `k` entries summing up to `goal`, in ascending order.
+  /// Each entry can only be used once, but an amount that is in the report twice can be used twice.
+  pub fn k_sum(&self, k: usize, goal: i64) -> Option<Vec<i64>> {
+    let mut found = Vec::new();
+    k_sum(&self.entries, k, goal, &mut Vec::new(), &mut found, true);
+    found.pop()
+  }
+
+  /// Returns all distinct combinations of `k` entries summing up to `goal`, each in ascending order.
+  pub fn k_sums(&self, k: usize, goal: i64) -> Vec<Vec<i64>> {
+    let mut found = Vec::new();
+    k_sum(&self.entries, k, goal, &mut Vec::new(), &mut found, false);
+    found
+  }
+
+  /// Prepares the search for subsets of any size summing up to `goal`, fails if the entries can
+  /// reach too many sums.
+  pub fn subset_sums(&self, goal: i64) -> Result<SubsetSums<'_>, SumRangeTooLarge> {
+    SubsetSums::new(&self.entries, goal)
+  }
 }
 
-fn sum2(set: &HashSet<i64>, goal: i64) -> Option<(i64, i64)> {
-  for &a in set.iter() {
-    let b = goal - a;
-    if a != b && set.contains(&b) {
-      return Some((a, b));
+/// Adds the combinations of `k` of the sorted `entries` summing up to `goal` to `found`, each
+/// extending `prefix`. Returns whether to stop because one combination was enough.
+fn k_sum(
+  entries: &[i64],
+  k: usize,
+  goal: i64,
+  prefix: &mut Vec<i64>,
+  found: &mut Vec<Vec<i64>>,
+  first_only: bool,
+) -> bool {
+  if k == 0 {
+    if goal == 0 {
+      found.push(prefix.clone());
     }
+    return first_only && !found.is_empty();
   }
-  None
+
+  if k == 1 {
+    if entries.binary_search(&goal).is_ok() {
+      prefix.push(goal);
+      found.push(prefix.clone());
+      prefix.pop();
+    }
+    return first_only && !found.is_empty();
+  }
+
+  for (i, &entry) in entries.iter().enumerate() {
+    // the same amount as before would only find the same combinations again
+    if i > 0 && entries[i - 1] == entry {
+      continue;
+    }
+    prefix.push(entry);
+    let stop = k_sum(
+      &entries[i + 1..],
+      k - 1,
+      goal - entry,
+      prefix,
+      found,
+      first_only,
+    );
+    prefix.pop();
+    if stop {
+      return true;
+    }
+  }
+  false
 }
 
-#[aoc(day1, part2)]
-pub fn solve_part2(input: &HashSet<i64>) -> Option<i64> {
-  sum3(input, GOAL).map(|(a, b, c)| a * b * c)
+/// At most this many sums are tracked, the counts of `SubsetSums::count` take 16 bytes each.
+const MAX_SUMS: u128 = 1 << 24;
+/// At most this many bits are used for the reachable sums of all suffixes of the entries.
+const MAX_TABLE_BITS: u128 = 1 << 30;
+
+/// The sums that the entries can reach span more values than `SubsetSums` tracks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SumRangeTooLarge {
+  pub sums: u128,
+  pub limit: u128,
+}
+
+impl fmt::Display for SumRangeTooLarge {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(
+      f,
+      "the subset sums span {} values, more than the limit of {}",
+      self.sums, self.limit
+    )
+  }
+}
+
+impl Error for SumRangeTooLarge {}
+
+/// The subsets of an expense report summing up to a goal, found by dynamic programming over the
+/// sums that the entries can reach. Building it takes time and memory proportional to the number
+/// of entries times the range of sums, the subsets themselves are only produced on demand.
+pub struct SubsetSums<'a> {
+  entries: &'a [i64],
+  goal: i64,
+  /// the smallest sum that is tracked
+  min_sum: i128,
+  /// the number of tracked sums
+  width: usize,
+  /// bit `s - min_sum` of `reachable[i]` is set if a subset of `entries[i..]` sums up to `s`
+  reachable: Vec<Vec<u64>>,
 }
 
-fn sum3(set: &HashSet<i64>, goal: i64) -> Option<(i64, i64, i64)> {
-  for &a in set.iter() {
-    let to_goal = goal - a;
-    if let Some((b, c)) = sum2(set, to_goal) {
-      if a != b && a != c {
-        return Some((a, b, c));
+impl<'a> SubsetSums<'a> {
+  fn new(entries: &'a [i64], goal: i64) -> Result<SubsetSums<'a>, SumRangeTooLarge> {
+    let min_sum = entries
+      .iter()
+      .filter(|&&e| e < 0)
+      .map(|&e| e as i128)
+      .sum::<i128>();
+    let mut max_sum = entries
+      .iter()
+      .filter(|&&e| e > 0)
+      .map(|&e| e as i128)
+      .sum::<i128>();
+    if min_sum == 0 {
+      // without negative entries no sum above the goal can come back to it
+      max_sum = max_sum.min(goal as i128);
+    }
+
+    let sums = (max_sum - min_sum + 1).max(0) as u128;
+    let limit = MAX_SUMS.min(MAX_TABLE_BITS / (entries.len() as u128 + 1));
+    if sums > limit {
+      return Err(SumRangeTooLarge { sums, limit });
+    }
+
+    let width = sums as usize;
+    let mut last = vec![0; width.div_ceil(64)];
+    if width > 0 {
+      set_bit(&mut last, (-min_sum) as usize);
+    }
+    let mut reachable = vec![last];
+    for &entry in entries.iter().rev() {
+      let next = reachable.last().unwrap();
+      let mut row = next.clone();
+      or_shifted(&mut row, next, entry as i128, width);
+      reachable.push(row);
+    }
+    reachable.reverse();
+
+    Ok(SubsetSums {
+      entries,
+      goal,
+      min_sum,
+      width,
+      reachable,
+    })
+  }
+
+  fn index(&self, sum: i128) -> Option<usize> {
+    let s = sum - self.min_sum;
+    if s >= 0 && s < self.width as i128 {
+      Some(s as usize)
+    } else {
+      None
+    }
+  }
+
+  /// Returns whether a subset of `entries[i..]` sums up to `sum`.
+  fn reachable(&self, i: usize, sum: i128) -> bool {
+    self
+      .index(sum)
+      .is_some_and(|s| get_bit(&self.reachable[i], s))
+  }
+
+  /// The order in which a table over the sums has to be updated in place when adding `entry`, so
+  /// that the sum without the entry is read before it is updated itself.
+  fn update_order(&self, entry: i64) -> impl Iterator<Item = usize> {
+    if entry > 0 {
+      Either::Left((0..self.width).rev())
+    } else {
+      Either::Right(0..self.width)
+    }
+  }
+
+  /// Returns the number of subsets, entries with the same amount count as different entries.
+  /// Saturates at `u128::MAX`.
+  pub fn count(&self) -> u128 {
+    let goal = match self.index(self.goal as i128) {
+      Some(goal) => goal,
+      None => return 0,
+    };
+    let mut counts = vec![0u128; self.width];
+    counts[(-self.min_sum) as usize] = 1;
+    for &entry in self.entries {
+      for s in self.update_order(entry) {
+        if let Some(rest) = self.index(s as i128 + self.min_sum - entry as i128) {
+          counts[s] = counts[s].saturating_add(counts[rest]);
+        }
+      }
+    }
+    counts[goal]
+  }
+
+  /// Returns a subset with the fewest entries, in ascending order.
+  pub fn fewest(&self) -> Option<Vec<i64>> {
+    if !self.reachable(0, self.goal as i128) {
+      return None;
+    }
+    // `fewest[s]` is the fewest of the entries so far summing up to the sum at `s`, bit `s` of
+    // `taken[i]` is set if entry `i` is part of such a subset of the entries up to `i`
+    let mut fewest = vec![usize::MAX; self.width];
+    fewest[(-self.min_sum) as usize] = 0;
+    let mut taken = Vec::with_capacity(self.entries.len());
+    for &entry in self.entries {
+      let mut bits = vec![0; self.width.div_ceil(64)];
+      for s in self.update_order(entry) {
+        let with = self
+          .index(s as i128 + self.min_sum - entry as i128)
+          .map(|rest| fewest[rest])
+          .filter(|&rest| rest != usize::MAX)
+          .map(|rest| rest + 1);
+        if let Some(with) = with.filter(|&with| with < fewest[s]) {
+          fewest[s] = with;
+          set_bit(&mut bits, s);
+        }
       }
+      taken.push(bits);
     }
+
+    let mut subset = Vec::new();
+    let mut rest = self.goal as i128;
+    for (i, &entry) in self.entries.iter().enumerate().rev() {
+      if get_bit(&taken[i], self.index(rest).unwrap()) {
+        subset.push(entry);
+        rest -= entry as i128;
+      }
+    }
+    subset.reverse();
+    Some(subset)
+  }
+
+  /// Returns the product of the entries of `fewest`, or `None` if there is no subset or the
+  /// product doesn't fit into an `i128`.
+  pub fn fewest_product(&self) -> Option<i128> {
+    self
+      .fewest()?
+      .iter()
+      .try_fold(1i128, |product, &entry| product.checked_mul(entry as i128))
+  }
+
+  /// Returns all subsets lazily, each in ascending order.
+  /// Every step of the search leads to a subset, so no time is wasted on dead ends.
+  pub fn iter(&self) -> impl Iterator<Item = Vec<i64>> + '_ {
+    let mut stack = Vec::new();
+    if self.reachable(0, self.goal as i128) {
+      stack.push((0, self.goal as i128, Vec::new()));
+    }
+    std::iter::from_fn(move || {
+      while let Some((i, rest, subset)) = stack.pop() {
+        if i == self.entries.len() {
+          return Some(subset);
+        }
+        let entry = self.entries[i];
+        if self.reachable(i + 1, rest) {
+          stack.push((i + 1, rest, subset.clone()));
+        }
+        if self.reachable(i + 1, rest - entry as i128) {
+          let mut subset = subset;
+          subset.push(entry);
+          stack.push((i + 1, rest - entry as i128, subset));
+        }
+      }
+      None
+    })
+  }
+}
+
+fn get_bit(bits: &[u64], i: usize) -> bool {
+  bits[i / 64] & (1 << (i % 64)) != 0
+}
+
+fn set_bit(bits: &mut [u64], i: usize) {
+  bits[i / 64] |= 1 << (i % 64);
+}
+
+/// Sets bit `i + shift` of `target` for every bit `i` set in `source`, keeping the first `width`
+/// bits.
+fn or_shifted(target: &mut [u64], source: &[u64], shift: i128, width: usize) {
+  if shift.unsigned_abs() >= width as u128 {
+    return;
+  }
+  let words = source.len();
+  let (word_shift, bit_shift) = (
+    (shift.unsigned_abs() / 64) as usize,
+    (shift.unsigned_abs() % 64) as u32,
+  );
+  for j in 0..words - word_shift {
+    let (to, from, carry) = if shift >= 0 {
+      (j + word_shift, j, j.checked_sub(1))
+    } else {
+      (
+        j,
+        j + word_shift,
+        Some(j + word_shift + 1).filter(|&k| k < words),
+      )
+    };
+    let mut word = if shift >= 0 {
+      source[from] << bit_shift
+    } else {
+      source[from] >> bit_shift
+    };
+    if let Some(carry) = carry.filter(|_| bit_shift > 0) {
+      word |= if shift >= 0 {
+        source[carry] >> (64 - bit_shift)
+      } else {
+        source[carry] << (64 - bit_shift)
+      };
+    }
+    target[to] |= word;
+  }
+  if !width.is_multiple_of(64) {
+    target[words - 1] &= (1 << (width % 64)) - 1;
+  }
+}
+
+#[aoc_generator(day1)]
+pub fn input_generator(input: &str) -> Result<ExpenseReport, ParseError> {
+  let entries = lines(input)
+    .map(|l| parse_at(DAY, input, l, "expected an expense entry"))
+    .collect::<Result<_, _>>()?;
+  Ok(ExpenseReport::new(entries))
+}
+
+#[aoc(day1, part1)]
+pub fn solve_part1(input: &ExpenseReport) -> Option<i64> {
+  input.k_sum(2, GOAL).map(|entries| entries.iter().product())
+}
+
+#[aoc(day1, part2)]
+pub fn solve_part2(input: &ExpenseReport) -> Option<i64> {
+  input.k_sum(3, GOAL).map(|entries| entries.iter().product())
+}
+
+pub struct Day01;
+
+impl Solution for Day01 {
+  const DAY: u8 = DAY;
+
+  type Input = ExpenseReport;
+
+  fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+    input_generator(input)
+  }
+
+  fn part1(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part1(input).into()
+  }
+
+  fn part2(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part2(input).into()
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::{input_generator, solve_part1, ExpenseReport};
+
+  #[test]
+  fn test_day01_duplicate_entries() {
+    let report = input_generator("1010\n979\n1010").unwrap();
+    assert_eq!(report.entries(), &[979, 1010, 1010]);
+    assert_eq!(solve_part1(&report), Some(1020100));
+    assert_eq!(solve_part1(&input_generator("1010\n979").unwrap()), None);
+  }
+
+  #[test]
+  fn test_day01_k_sums() {
+    let report = ExpenseReport::new(vec![1, 2, 2, 3, 4, -1]);
+    assert_eq!(report.k_sum(2, 4), Some(vec![1, 3]));
+    assert_eq!(report.k_sums(2, 4), vec![vec![1, 3], vec![2, 2]]);
+    assert_eq!(report.k_sums(3, 5), vec![vec![-1, 2, 4], vec![1, 2, 2]]);
+    assert_eq!(report.k_sums(0, 0), vec![Vec::<i64>::new()]);
+    assert_eq!(report.k_sum(4, 100), None);
+  }
+
+  #[test]
+  fn test_day01_subset_sums() {
+    let report = ExpenseReport::new(vec![1, 2, 2, 3, 4, -1]);
+    let sums = report.subset_sums(4).unwrap();
+    let mut subsets = sums.iter().collect::<Vec<_>>();
+    subsets.sort();
+    assert_eq!(
+      subsets,
+      vec![
+        vec![-1, 1, 2, 2],
+        vec![-1, 1, 4],
+        vec![-1, 2, 3],
+        vec![-1, 2, 3],
+        vec![1, 3],
+        vec![2, 2],
+        vec![4],
+      ]
+    );
+    assert_eq!(sums.count(), 7);
+    assert_eq!(sums.fewest(), Some(vec![4]));
+
+    let report = input_generator("1721\n979\n366\n299\n675\n1456").unwrap();
+    let sums = report.subset_sums(2020).unwrap();
+    assert_eq!(sums.count(), 2);
+    assert_eq!(sums.iter().count(), 2);
+    assert_eq!(sums.fewest_product(), Some(514579));
+    assert_eq!(report.subset_sums(-1).unwrap().count(), 0);
+    assert_eq!(report.subset_sums(-1).unwrap().iter().next(), None);
+    assert_eq!(report.subset_sums(0).unwrap().fewest(), Some(vec![]));
+  }
+
+  #[test]
+  fn test_day01_subset_sums_limits() {
+    let report = ExpenseReport::new(vec![1_000_000_000, 2_000_000_000, 5]);
+    let error = report.subset_sums(3_000_000_000).err().unwrap();
+    assert_eq!(error.sums, 3_000_000_001);
+    assert!(ExpenseReport::new(vec![i64::MIN, i64::MIN, 1])
+      .subset_sums(1)
+      .is_err());
+
+    let report = ExpenseReport::new(vec![i64::MAX, i64::MAX, 3, 1]);
+    let sums = report.subset_sums(4).unwrap();
+    assert_eq!(sums.count(), 1);
+    assert_eq!(sums.fewest(), Some(vec![1, 3]));
+    assert_eq!(report.subset_sums(i64::MIN).unwrap().fewest(), None);
+
+    let report = ExpenseReport::new(vec![1; 300]);
+    assert_eq!(
+      report.subset_sums(300).unwrap().fewest(),
+      Some(vec![1; 300])
+    );
+    assert_eq!(report.subset_sums(299).unwrap().count(), 300);
   }
-  None
 }
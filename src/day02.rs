@@ -1,48 +1,441 @@
+use crate::{
+  answer::Answer, diagnostics::Diagnostics, input::lines, parse_at, solution::Solution, ParseError,
+};
 use regex::Regex;
+use std::error::Error;
+use std::fmt;
+use unicode_segmentation::{Graphemes, UnicodeSegmentation};
 
+const DAY: u8 = 2;
+
+/// An entry of the password database. Letters are grapheme clusters, so a letter with a combining
+/// accent is a single letter for counts and positions.
 pub struct Password {
   a: usize,
   b: usize,
-  letter: char,
+  letter: String,
   password: String,
 }
 
 impl Password {
-  pub fn is_valid_policy_1(&self) -> bool {
-    let count = self.password.chars().filter(|c| *c == self.letter).count();
-    return count >= self.a && count <= self.b;
+  pub fn letter(&self) -> &str {
+    &self.letter
+  }
+
+  pub fn password(&self) -> &str {
+    &self.password
+  }
+
+  pub fn letters(&self) -> Graphemes<'_> {
+    self.password.graphemes(true)
+  }
+
+  /// Returns the letter at the 1-based `position`.
+  pub fn letter_at(&self, position: usize) -> Result<&str, PositionError> {
+    let i = position.checked_sub(1).ok_or(PositionError::Zero)?;
+    self
+      .letters()
+      .nth(i)
+      .ok_or_else(|| PositionError::OutOfRange {
+        position,
+        length: self.letters().count(),
+      })
+  }
+
+  pub fn check(&self, policy: &dyn PasswordPolicy) -> Verdict {
+    policy.check(self)
+  }
+}
+
+/// A position of an entry that doesn't point at a letter of its password.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PositionError {
+  /// Positions are 1-based.
+  Zero,
+  OutOfRange {
+    position: usize,
+    length: usize,
+  },
+}
+
+impl fmt::Display for PositionError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      PositionError::Zero => write!(f, "position 0, positions start at 1"),
+      PositionError::OutOfRange { position, length } => write!(
+        f,
+        "position {} is outside of the password of length {}",
+        position, length
+      ),
+    }
+  }
+}
+
+impl Error for PositionError {}
+
+/// The outcome of checking an entry against a policy, explaining why an entry is invalid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+  Valid,
+  /// The letter occurs `count` times instead of `min..=max` times.
+  WrongCount {
+    letter: String,
+    count: usize,
+    min: usize,
+    max: usize,
+  },
+  /// The letter has to be at exactly one of the 1-based `positions`, but is at `found` of them.
+  WrongPositions {
+    letter: String,
+    positions: (usize, usize),
+    found: usize,
+  },
+  InvalidPosition(PositionError),
+  /// The password doesn't match `pattern`.
+  PatternMismatch {
+    pattern: String,
+  },
+  /// The password contains `letter` at the 1-based `position`.
+  ForbiddenLetter {
+    letter: String,
+    position: usize,
+  },
+}
+
+impl Verdict {
+  pub fn is_valid(&self) -> bool {
+    *self == Verdict::Valid
+  }
+}
+
+impl fmt::Display for Verdict {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Verdict::Valid => write!(f, "valid"),
+      Verdict::WrongCount {
+        letter,
+        count,
+        min,
+        max,
+      } => write!(
+        f,
+        "`{}` occurs {} times, expected {} to {}",
+        letter, count, min, max
+      ),
+      Verdict::WrongPositions {
+        letter,
+        positions,
+        found,
+      } => write!(
+        f,
+        "`{}` is at {} of positions {} and {}, expected exactly one",
+        letter, found, positions.0, positions.1
+      ),
+      Verdict::InvalidPosition(error) => error.fmt(f),
+      Verdict::PatternMismatch { pattern } => write!(f, "doesn't match `{}`", pattern),
+      Verdict::ForbiddenLetter { letter, position } => {
+        write!(f, "forbidden `{}` at position {}", letter, position)
+      }
+    }
+  }
+}
+
+/// A rule that the entries of a password database have to follow.
+pub trait PasswordPolicy {
+  fn check(&self, entry: &Password) -> Verdict;
+}
+
+/// The policy of the sled rental place: the letter occurs between the two numbers of times.
+pub struct SledRentalPolicy;
+
+impl PasswordPolicy for SledRentalPolicy {
+  fn check(&self, entry: &Password) -> Verdict {
+    let count = entry.letters().filter(|&l| l == entry.letter).count();
+    if count >= entry.a && count <= entry.b {
+      Verdict::Valid
+    } else {
+      Verdict::WrongCount {
+        letter: entry.letter.clone(),
+        count,
+        min: entry.a,
+        max: entry.b,
+      }
+    }
+  }
+}
+
+/// The policy of the Toboggan Corporate: the letter is at exactly one of the two 1-based positions.
+pub struct TobogganPolicy;
+
+impl PasswordPolicy for TobogganPolicy {
+  fn check(&self, entry: &Password) -> Verdict {
+    let mut found = 0;
+    for &position in &[entry.a, entry.b] {
+      match entry.letter_at(position) {
+        Ok(letter) => found += (letter == entry.letter) as usize,
+        Err(error) => return Verdict::InvalidPosition(error),
+      }
+    }
+    if found == 1 {
+      Verdict::Valid
+    } else {
+      Verdict::WrongPositions {
+        letter: entry.letter.clone(),
+        positions: (entry.a, entry.b),
+        found,
+      }
+    }
   }
+}
+
+/// The whole password has to match a regular expression.
+pub struct RegexPolicy {
+  /// the pattern as passed to `new`, without the anchors
+  pattern: String,
+  regex: Regex,
+}
+
+impl RegexPolicy {
+  pub fn new(pattern: &str) -> Result<RegexPolicy, regex::Error> {
+    Ok(RegexPolicy {
+      pattern: pattern.to_string(),
+      regex: Regex::new(&format!("^(?:{})$", pattern))?,
+    })
+  }
+}
 
-  pub fn is_valid_policy_2(&self) -> bool {
-    let chars = self.password.chars().collect::<Vec<_>>();
-    (chars[self.a - 1] == self.letter) ^ (chars[self.b - 1] == self.letter)
+impl PasswordPolicy for RegexPolicy {
+  fn check(&self, entry: &Password) -> Verdict {
+    if self.regex.is_match(&entry.password) {
+      Verdict::Valid
+    } else {
+      Verdict::PatternMismatch {
+        pattern: self.pattern.clone(),
+      }
+    }
   }
 }
 
+/// The password must not contain a letter.
+pub struct ForbiddenLetterPolicy {
+  pub letter: String,
+}
+
+impl PasswordPolicy for ForbiddenLetterPolicy {
+  fn check(&self, entry: &Password) -> Verdict {
+    match entry.letters().position(|l| l == self.letter) {
+      Some(i) => Verdict::ForbiddenLetter {
+        letter: self.letter.clone(),
+        position: i + 1,
+      },
+      None => Verdict::Valid,
+    }
+  }
+}
+
+/// Returns the index and verdict of every entry violating `policy`.
+pub fn failures(passwords: &[Password], policy: &dyn PasswordPolicy) -> Vec<(usize, Verdict)> {
+  passwords
+    .iter()
+    .map(|p| p.check(policy))
+    .enumerate()
+    .filter(|(_, verdict)| !verdict.is_valid())
+    .collect()
+}
+
 #[aoc_generator(day2)]
-pub fn input_generator(input: &str) -> Vec<Password> {
-  let re = Regex::new(r"^(?P<a>\d+)-(?P<b>\d+) (?P<letter>[a-z]): (?P<password>[a-z]+)$").unwrap();
+pub fn input_generator(input: &str) -> Result<Vec<Password>, ParseError> {
+  let re = Regex::new(r"^(?P<a>\d+)-(?P<b>\d+) (?P<letter>\S+): (?P<password>\S.*)$").unwrap();
 
-  input
-    .lines()
+  lines(input)
     .map(|line| {
-      let captures = re.captures(line).unwrap();
-      Password {
-        a: captures["a"].parse::<usize>().unwrap(),
-        b: captures["b"].parse::<usize>().unwrap(),
-        letter: captures["letter"].chars().next().unwrap(),
-        password: captures["password"].to_string(),
+      let captures = re.captures(line).ok_or_else(|| {
+        ParseError::new(
+          DAY,
+          input,
+          line,
+          "expected `<min>-<max> <letter>: <password>`",
+        )
+      })?;
+      let letter = captures.name("letter").unwrap().as_str();
+      // a letter may carry combining accents, but has to start with an alphabetic character
+      if letter.graphemes(true).count() != 1 || !letter.starts_with(char::is_alphabetic) {
+        return Err(ParseError::new(
+          DAY,
+          input,
+          letter,
+          "expected a single letter",
+        ));
       }
+      Ok(Password {
+        a: parse_at(
+          DAY,
+          input,
+          captures.name("a").unwrap().as_str(),
+          "invalid position",
+        )?,
+        b: parse_at(
+          DAY,
+          input,
+          captures.name("b").unwrap().as_str(),
+          "invalid position",
+        )?,
+        letter: letter.to_string(),
+        password: captures["password"].to_string(),
+      })
     })
     .collect()
 }
 
 #[aoc(day2, part1)]
 pub fn solve_part1(passwords: &[Password]) -> usize {
-  passwords.iter().filter(|p| p.is_valid_policy_1()).count()
+  passwords
+    .iter()
+    .filter(|p| p.check(&SledRentalPolicy).is_valid())
+    .count()
 }
 
 #[aoc(day2, part2)]
 pub fn solve_part2(passwords: &[Password]) -> usize {
-  passwords.iter().filter(|p| p.is_valid_policy_2()).count()
+  passwords
+    .iter()
+    .filter(|p| p.check(&TobogganPolicy).is_valid())
+    .count()
+}
+
+pub struct Day02;
+
+impl Solution for Day02 {
+  const DAY: u8 = DAY;
+
+  type Input = Vec<Password>;
+
+  fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+    input_generator(input)
+  }
+
+  fn part1(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part1(input).into()
+  }
+
+  fn part2(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part2(input).into()
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::{
+    failures, input_generator, ForbiddenLetterPolicy, PositionError, RegexPolicy, SledRentalPolicy,
+    TobogganPolicy, Verdict,
+  };
+
+  const TEST_INPUT: &str = "1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc\n2-7 c: abcc";
+
+  #[test]
+  fn test_day02_verdicts() {
+    let passwords = input_generator(TEST_INPUT).unwrap();
+    assert_eq!(
+      failures(&passwords, &SledRentalPolicy),
+      vec![(
+        1,
+        Verdict::WrongCount {
+          letter: String::from("b"),
+          count: 0,
+          min: 1,
+          max: 3
+        }
+      )]
+    );
+    let failed = failures(&passwords, &TobogganPolicy);
+    assert_eq!(
+      failed[1],
+      (
+        2,
+        Verdict::WrongPositions {
+          letter: String::from("c"),
+          positions: (2, 9),
+          found: 2
+        }
+      )
+    );
+    assert_eq!(
+      failed[2].1,
+      Verdict::InvalidPosition(PositionError::OutOfRange {
+        position: 7,
+        length: 4
+      })
+    );
+    assert_eq!(
+      failed[2].1.to_string(),
+      "position 7 is outside of the password of length 4"
+    );
+
+    let policy = RegexPolicy::new("[a-e]+").unwrap();
+    assert_eq!(
+      failures(&passwords, &policy)
+        .iter()
+        .map(|(i, _)| *i)
+        .collect::<Vec<_>>(),
+      vec![1]
+    );
+    assert_eq!(
+      passwords[1].check(&policy).to_string(),
+      "doesn't match `[a-e]+`"
+    );
+    assert_eq!(
+      passwords[3].check(&ForbiddenLetterPolicy {
+        letter: String::from("c")
+      }),
+      Verdict::ForbiddenLetter {
+        letter: String::from("c"),
+        position: 3
+      }
+    );
+  }
+
+  #[test]
+  fn test_day02_unicode() {
+    // `e\u{301}` is an `é` written with a combining accent, which is a single letter
+    let input = "1-3 e\u{301}: xe\u{301}e\u{301}\n1-6 ß: straße\n0-4 ж: aжbж\n2-9 a: äb";
+    let passwords = input_generator(input).unwrap();
+    assert_eq!(
+      failures(&passwords, &SledRentalPolicy),
+      vec![(
+        3,
+        Verdict::WrongCount {
+          letter: String::from("a"),
+          count: 0,
+          min: 2,
+          max: 9
+        }
+      )]
+    );
+    assert_eq!(
+      failures(&passwords, &TobogganPolicy),
+      vec![
+        (
+          1,
+          Verdict::WrongPositions {
+            letter: String::from("ß"),
+            positions: (1, 6),
+            found: 0
+          }
+        ),
+        (2, Verdict::InvalidPosition(PositionError::Zero)),
+        (
+          3,
+          Verdict::InvalidPosition(PositionError::OutOfRange {
+            position: 9,
+            length: 2
+          })
+        ),
+      ]
+    );
+    assert_eq!(passwords[0].letter_at(3), Ok("e\u{301}"));
+    assert!(input_generator("1-3 ab: abc").is_err());
+    let error = input_generator("1-3 a: abc\n1-3 #: ###").err().unwrap();
+    assert_eq!((error.line, error.column, error.text.as_str()), (2, 5, "#"));
+    assert!(input_generator("1-3 😀: a😀b").is_err());
+  }
 }
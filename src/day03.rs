@@ -1,3 +1,11 @@
+use crate::{answer::Answer, diagnostics::Diagnostics, grid::Grid, solution::Solution, ParseError};
+use plotters::prelude::*;
+use std::error::Error;
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+const DAY: u8 = 3;
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Tile {
   Tree,
@@ -5,71 +13,391 @@ pub enum Tile {
 }
 
 pub struct TreeMap {
-  data: Vec<Vec<Tile>>,
+  grid: Grid<Tile>,
+}
+
+/// A direction of travel, `right` columns for every `down` rows. Negative values go left or up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Slope {
+  pub right: i64,
+  pub down: i64,
+}
+
+impl Slope {
+  pub fn new(right: i64, down: i64) -> Slope {
+    Slope { right, down }
+  }
+}
+
+/// Which points of the line along a slope are visited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sampling {
+  /// Only the points reached by whole steps of the slope, like the toboggan does.
+  Steps,
+  /// One point in every row the line passes, with the column rounded down like in Bresenham's
+  /// line algorithm, so a slope of `right: 1, down: 2` visits every row.
+  EveryRow,
 }
 
 impl TreeMap {
-  fn height(&self) -> usize {
-    self.data.len()
+  pub fn width(&self) -> usize {
+    self.grid.width()
   }
 
-  fn width(&self) -> usize {
-    self.data[0].len()
+  pub fn height(&self) -> usize {
+    self.grid.height()
   }
 
-  fn has_tree_at_position(&self, x: usize, y: usize) -> bool {
-    y < self.height() && self.get_tile_at_position(x, y) == Tile::Tree
+  /// Returns whether there is a tree at `(x, y)` of the map repeated to the left and right.
+  pub fn has_tree_at_position(&self, x: i64, y: i64) -> bool {
+    y >= 0
+      && y < self.height() as i64
+      && *self.grid.get_wrapping(x as isize, y as isize) == Tile::Tree
   }
 
-  fn get_tile_at_position(&self, x: usize, y: usize) -> Tile {
-    self.data[y][x % self.width()]
+  /// Returns the positions visited along `slope` until the path leaves the map at the top or the
+  /// bottom. `x` is not wrapped, so it tells how often the map repeats along the path.
+  /// Paths going down start in the top left corner, paths going up in the bottom left one. A
+  /// horizontal path ends before it comes back to the same position of the repeated map.
+  pub fn path(&self, slope: Slope, sampling: Sampling) -> Vec<(i64, i64)> {
+    let height = self.height() as i64;
+    let start_y = if slope.down < 0 { height - 1 } else { 0 };
+
+    if slope.down == 0 {
+      let width = self.width() as i64;
+      let period = width / gcd(slope.right.abs(), width);
+      return (0..period).map(|k| (k * slope.right, start_y)).collect();
+    }
+
+    let steps = match sampling {
+      Sampling::Steps => (0..)
+        .map(|k| (k * slope.right, k * slope.down))
+        .take_while(|&(_, dy)| dy.abs() < height)
+        .collect::<Vec<_>>(),
+      Sampling::EveryRow => (0..height)
+        .map(|row| {
+          (
+            (row * slope.right).div_euclid(slope.down.abs()),
+            row * slope.down.signum(),
+          )
+        })
+        .collect(),
+    };
+    steps
+      .into_iter()
+      .map(|(dx, dy)| (dx, start_y + dy))
+      .collect()
   }
 
-  fn tree_count(&self, slope_x: usize, slope_y: usize) -> usize {
-    let mut x = 0;
-    let mut y = 0;
+  pub fn tree_count(&self, slope: Slope, sampling: Sampling) -> usize {
+    self
+      .path(slope, sampling)
+      .into_iter()
+      .filter(|&(x, y)| self.has_tree_at_position(x, y))
+      .count()
+  }
 
-    let mut tree_count = 0;
+  /// Returns the number of trees along each of the `slopes`.
+  pub fn tree_counts(&self, slopes: &[Slope], sampling: Sampling) -> Vec<usize> {
+    slopes
+      .iter()
+      .map(|&slope| self.tree_count(slope, sampling))
+      .collect()
+  }
+
+  /// Returns the slope with `right` and `down` within the given bounds that passes the fewest
+  /// trees, preferring the first one in the order of `rights` and then `downs`.
+  /// Only slopes going down are tried, the others never leave the first row and pass no trees.
+  pub fn find_slope_with_fewest_trees(
+    &self,
+    rights: RangeInclusive<i64>,
+    downs: RangeInclusive<i64>,
+    sampling: Sampling,
+  ) -> Option<(Slope, usize)> {
+    let downs = (*downs.start()).max(1)..=*downs.end();
+    rights
+      .flat_map(|right| downs.clone().map(move |down| Slope::new(right, down)))
+      .map(|slope| (slope, self.tree_count(slope, sampling)))
+      .fold(
+        None,
+        |best: Option<(Slope, usize)>, (slope, count)| match best {
+          Some((_, best_count)) if best_count <= count => best,
+          _ => Some((slope, count)),
+        },
+      )
+  }
+
+  /// Returns the paths along `slopes` and the first column of the repeated map they need, which
+  /// is the first column of a whole repetition.
+  fn routes(&self, slopes: &[Slope], sampling: Sampling) -> (Vec<Vec<(i64, i64)>>, i64, i64) {
+    let width = self.width() as i64;
+    let paths = slopes
+      .iter()
+      .map(|&slope| self.path(slope, sampling))
+      .collect::<Vec<_>>();
+    let xs = || paths.iter().flatten().map(|&(x, _)| x);
+    let first_x = xs().min().unwrap_or(0).div_euclid(width) * width;
+    let end_x = (xs().max().unwrap_or(0).div_euclid(width) + 1) * width;
+    (paths, first_x, end_x)
+  }
+
+  /// Returns the map with the paths along `slopes` marked, `X` where a path hits a tree and `O`
+  /// where it doesn't. The map is repeated to the left and right as far as the paths go.
+  pub fn route_map(&self, slopes: &[Slope], sampling: Sampling) -> Grid<char> {
+    let (paths, first_x, end_x) = self.routes(slopes, sampling);
+    let mut routes = Grid::from_fn((end_x - first_x) as usize, self.height(), |x, y| match self
+      .grid
+      .get_wrapping(x as isize + first_x as isize, y as isize)
+    {
+      Tile::Tree => '#',
+      Tile::Free => '.',
+    });
+    for (x, y) in paths.into_iter().flatten() {
+      routes[((x - first_x) as usize, y as usize)] = if self.has_tree_at_position(x, y) {
+        'X'
+      } else {
+        'O'
+      };
+    }
+    routes
+  }
+
+  /// Draws the `route_map` as a PNG image at `path` with square cells of `cell_size` pixels.
+  /// Trees are green, and each route is a line of its own color with crosses where it hits a
+  /// tree and circles where it doesn't.
+  pub fn draw_routes(
+    &self,
+    slopes: &[Slope],
+    sampling: Sampling,
+    path: &Path,
+    cell_size: u32,
+  ) -> Result<(), Box<dyn Error>> {
+    let (paths, first_x, end_x) = self.routes(slopes, sampling);
+    let size = (
+      (end_x - first_x) as u32 * cell_size,
+      self.height() as u32 * cell_size,
+    );
+    let area = BitMapBackend::new(path, size).into_drawing_area();
+    area.fill(&WHITE)?;
+
+    let cell = cell_size as i32;
+    let corner = |x: i64, y: i64| ((x - first_x) as i32 * cell, y as i32 * cell);
+    let center = |x: i64, y: i64| {
+      let (x, y) = corner(x, y);
+      (x + cell / 2, y + cell / 2)
+    };
+
+    for x in first_x..end_x {
+      for y in 0..self.height() as i64 {
+        if self.has_tree_at_position(x, y) {
+          let (left, top) = corner(x, y);
+          area.draw(&Rectangle::new(
+            [(left, top), (left + cell, top + cell)],
+            GREEN.mix(0.6).filled(),
+          ))?;
+        }
+      }
+    }
 
-    while y < self.height() {
-      if self.has_tree_at_position(x, y) {
-        tree_count += 1;
+    let marker_size = (cell / 3).max(1);
+    for (i, points) in paths.iter().enumerate() {
+      let color = Palette99::pick(i).to_rgba();
+      area.draw(&PathElement::new(
+        points
+          .iter()
+          .map(|&(x, y)| center(x, y))
+          .collect::<Vec<_>>(),
+        color.stroke_width(1),
+      ))?;
+      for &(x, y) in points {
+        if self.has_tree_at_position(x, y) {
+          area.draw(&Cross::new(
+            center(x, y),
+            marker_size,
+            color.stroke_width(2),
+          ))?;
+        } else {
+          area.draw(&Circle::new(
+            center(x, y),
+            marker_size,
+            color.stroke_width(1),
+          ))?;
+        }
       }
-      x += slope_x;
-      y += slope_y;
     }
 
-    tree_count
+    area.present()?;
+    Ok(())
+  }
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+  if b == 0 {
+    a
+  } else {
+    gcd(b, a % b)
   }
 }
 
 #[aoc_generator(day3)]
-pub fn input_generator(input: &str) -> TreeMap {
-  let map_data = input
-    .lines()
-    .map(|line| {
-      line
-        .chars()
-        .map(|c| match c {
-          '#' => Tile::Tree,
-          _ => Tile::Free,
-        })
-        .collect::<Vec<Tile>>()
-    })
-    .collect();
+pub fn input_generator(input: &str) -> Result<TreeMap, ParseError> {
+  let grid = Grid::parse(DAY, input, input, "expected `#` or `.`", |c| match c {
+    '#' => Some(Tile::Tree),
+    '.' => Some(Tile::Free),
+    _ => None,
+  })?;
 
-  TreeMap { data: map_data }
+  Ok(TreeMap { grid })
 }
 
 #[aoc(day3, part1)]
 pub fn solve_part1(tree_map: &TreeMap) -> usize {
-  tree_map.tree_count(3, 1)
+  tree_map.tree_count(Slope::new(3, 1), Sampling::Steps)
 }
 
+const PART2_SLOPES: [Slope; 5] = [
+  Slope { right: 1, down: 1 },
+  Slope { right: 3, down: 1 },
+  Slope { right: 5, down: 1 },
+  Slope { right: 7, down: 1 },
+  Slope { right: 1, down: 2 },
+];
+
 #[aoc(day3, part2)]
 pub fn solve_part2(tree_map: &TreeMap) -> usize {
-  [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)]
+  tree_map
+    .tree_counts(&PART2_SLOPES, Sampling::Steps)
     .iter()
-    .map(|(slope_x, slope_y)| tree_map.tree_count(*slope_x, *slope_y))
-    .fold(1, |acc, tree_count| acc * tree_count)
+    .product()
+}
+
+pub struct Day03;
+
+impl Solution for Day03 {
+  const DAY: u8 = DAY;
+
+  type Input = TreeMap;
+
+  fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+    input_generator(input)
+  }
+
+  fn part1(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part1(input).into()
+  }
+
+  fn part2(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part2(input).into()
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::{input_generator, Sampling, Slope};
+
+  const TEST_INPUT: &str = "..##.......\n\
+  #...#...#..\n\
+  .#....#..#.\n\
+  ..#.#...#.#\n\
+  .#...##..#.\n\
+  ..#.##.....\n\
+  .#.#.#....#\n\
+  .#........#\n\
+  #.##...#...\n\
+  #...##....#\n\
+  .#..#...#.#";
+
+  #[test]
+  fn test_day03_paths() {
+    let map = input_generator("#..\n.#.\n#.#").unwrap();
+    assert_eq!(
+      map.path(Slope::new(1, 2), Sampling::Steps),
+      vec![(0, 0), (1, 2)]
+    );
+    assert_eq!(
+      map.path(Slope::new(1, 2), Sampling::EveryRow),
+      vec![(0, 0), (0, 1), (1, 2)]
+    );
+    assert_eq!(
+      map.path(Slope::new(-1, -1), Sampling::Steps),
+      vec![(0, 2), (-1, 1), (-2, 0)]
+    );
+    assert_eq!(
+      map.path(Slope::new(-1, 2), Sampling::EveryRow),
+      vec![(0, 0), (-1, 1), (-1, 2)]
+    );
+    assert_eq!(
+      map.path(Slope::new(2, 0), Sampling::Steps),
+      vec![(0, 0), (2, 0), (4, 0)]
+    );
+    assert_eq!(map.tree_count(Slope::new(-1, -1), Sampling::Steps), 1);
+  }
+
+  #[test]
+  fn test_day03_route_map() {
+    let map = input_generator(TEST_INPUT).unwrap();
+    // like in the puzzle text, but with the start marked as well
+    let expected = "O.##.........##.........##.......\n\
+    #..O#...#..#...#...#..#...#...#..\n\
+    .#....X..#..#....#..#..#....#..#.\n\
+    ..#.#...#O#..#.#...#.#..#.#...#.#\n\
+    .#...##..#..X...##..#..#...##..#.\n\
+    ..#.##.......#.X#.......#.##.....\n\
+    .#.#.#....#.#.#.#.O..#.#.#.#....#\n\
+    .#........#.#........X.#........#\n\
+    #.##...#...#.##...#...#.X#...#...\n\
+    #...##....##...##....##...#X....#\n\
+    .#..#...#.#.#..#...#.#.#..#...X.#";
+    let routes = map.route_map(&[Slope::new(3, 1)], Sampling::Steps);
+    assert_eq!(routes.to_string(), expected);
+
+    let left = map.route_map(&[Slope::new(-1, 1), Slope::new(1, 1)], Sampling::Steps);
+    assert_eq!((left.width(), left.height()), (22, 11));
+    assert_eq!(left[(11, 0)], 'O');
+    assert_eq!(left[(10, 1)], 'O');
+    assert_eq!(left[(9, 2)], 'X');
+    assert_eq!(left[(12, 1)], 'O');
+  }
+
+  #[test]
+  fn test_day03_draw_routes() {
+    let map = input_generator(TEST_INPUT).unwrap();
+    let path = std::env::temp_dir().join("aoc2020_day03_routes.png");
+    map
+      .draw_routes(
+        &[Slope::new(3, 1), Slope::new(-1, 2)],
+        Sampling::Steps,
+        &path,
+        4,
+      )
+      .unwrap();
+    let png = std::fs::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(png.starts_with(b"\x89PNG"));
+  }
+
+  #[test]
+  fn test_day03_fewest_trees() {
+    let map = input_generator(TEST_INPUT).unwrap();
+    assert_eq!(map.tree_count(Slope::new(3, 1), Sampling::Steps), 7);
+    assert_eq!(
+      map.tree_counts(&[Slope::new(1, 1), Slope::new(1, 2)], Sampling::Steps),
+      vec![2, 2]
+    );
+    let (slope, count) = map
+      .find_slope_with_fewest_trees(0..=7, 1..=2, Sampling::Steps)
+      .unwrap();
+    assert_eq!(count, map.tree_count(slope, Sampling::Steps));
+    assert!((0..=7)
+      .flat_map(|right| (1..=2).map(move |down| Slope::new(right, down)))
+      .all(|slope| map.tree_count(slope, Sampling::Steps) >= count));
+    assert_eq!(
+      map.find_slope_with_fewest_trees(0..=7, -1..=2, Sampling::Steps),
+      Some((slope, count))
+    );
+    assert_eq!(
+      map.find_slope_with_fewest_trees(0..=7, -2..=0, Sampling::Steps),
+      None
+    );
+  }
 }
@@ -1,115 +1,403 @@
+use crate::{
+  answer::Answer, diagnostics::Diagnostics, input::blocks, solution::Solution, ParseError,
+};
 use regex::Regex;
-use std::collections::HashMap;
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
 
-#[derive(Debug)]
-pub struct Passport {
-  fields: HashMap<String, String>,
-}
+const DAY: u8 = 4;
+
+/// The rules of the puzzle, used unless another schema is given.
+pub const DEFAULT_SCHEMA: &str = include_str!("../input/2020/passport_schema.json");
 
 lazy_static! {
-  static ref HAIR_COLOR_REGEX: Regex = Regex::new(r"^#[0-9a-f]{6}$").unwrap();
+  static ref DEFAULT: Schema = Schema::from_json(DEFAULT_SCHEMA).unwrap();
 }
 
-impl Passport {
-  const MANDANTORY_FIELDS: &'static [&'static str] =
-    &["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid"];
+/// The fields a kind of document may have and the rules their values have to follow, written as
+/// JSON like `input/2020/passport_schema.json`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Schema {
+  pub fields: BTreeMap<String, FieldRule>,
+}
 
-  fn has_all_mandatory_fields(&self) -> bool {
-    Passport::MANDANTORY_FIELDS
+impl Schema {
+  pub fn from_json(json: &str) -> Result<Schema, serde_json::Error> {
+    serde_json::from_str(json)
+  }
+
+  pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Schema, Box<dyn Error>> {
+    Ok(Schema::from_json(&fs::read_to_string(path)?)?)
+  }
+
+  /// The schema of the puzzle, see `DEFAULT_SCHEMA`.
+  pub fn default_schema() -> &'static Schema {
+    &DEFAULT
+  }
+
+  pub fn required_fields(&self) -> impl Iterator<Item = &str> {
+    self
+      .fields
       .iter()
-      .all(|field| self.fields.contains_key(&field.to_string()))
+      .filter(|(_, rule)| rule.required)
+      .map(|(key, _)| key.as_str())
   }
+}
 
-  fn is_valid(&self) -> bool {
-    Passport::MANDANTORY_FIELDS.iter().all(|key| {
-      self
-        .fields
-        .get(&key.to_string())
-        .map(|field| self.validate_field(key, field))
-        .unwrap_or(false)
-    })
-  }
-
-  fn validate_field(&self, key: &str, value: &str) -> bool {
-    match key {
-      "byr" => self.validate_year(value, 1920, 2002),
-      "iyr" => self.validate_year(value, 2010, 2020),
-      "eyr" => self.validate_year(value, 2020, 2030),
-      "hgt" => self.validate_height(value),
-      "hcl" => self.validate_hair_color(value),
-      "ecl" => self.validate_eye_color(value),
-      "pid" => self.validate_passport_id(value),
-      _ => panic!("Invalid key: `{}`", key),
+#[derive(Debug, Deserialize)]
+pub struct FieldRule {
+  #[serde(default)]
+  pub required: bool,
+  #[serde(flatten)]
+  pub kind: FieldKind,
+}
+
+/// The type of a field value and its constraints, all of which are optional.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum FieldKind {
+  /// Any text, which has to match `pattern` completely.
+  Text { pattern: Option<Pattern> },
+  /// A number of exactly `digits` decimal digits.
+  Integer {
+    digits: Option<usize>,
+    min: Option<i64>,
+    max: Option<i64>,
+  },
+  /// One of the given `values`.
+  Enum { values: Vec<String> },
+  /// A number directly followed by one of the `units`, like `183cm`.
+  Measure { units: BTreeMap<String, Range> },
+}
+
+impl FieldKind {
+  pub fn is_valid(&self, value: &str) -> bool {
+    self.check(value).is_ok()
+  }
+
+  /// Returns why `value` isn't valid, like `exceeds 76in`.
+  pub fn check(&self, value: &str) -> Result<(), String> {
+    match self {
+      FieldKind::Text { pattern: None } => Ok(()),
+      FieldKind::Text {
+        pattern: Some(pattern),
+      } => {
+        if pattern.regex.is_match(value) {
+          Ok(())
+        } else {
+          Err(format!("doesn't match `{}`", pattern.source))
+        }
+      }
+      FieldKind::Integer { digits, min, max } => {
+        let n = parse_integer(value).ok_or("is not a number")?;
+        match digits {
+          Some(digits) if value.len() != *digits => Err(format!("doesn't have {} digits", digits)),
+          _ => Range::new(*min, *max).check(n, ""),
+        }
+      }
+      FieldKind::Enum { values } => {
+        if values.iter().any(|v| v == value) {
+          Ok(())
+        } else {
+          Err(format!("is not one of {}", values.join(", ")))
+        }
+      }
+      FieldKind::Measure { units } => {
+        let split = value
+          .find(|c: char| !c.is_ascii_digit())
+          .unwrap_or(value.len());
+        let (number, unit) = value.split_at(split);
+        let n = parse_integer(number).ok_or("doesn't start with a number")?;
+        let range = units.get(unit).ok_or_else(|| {
+          let expected = units.keys().cloned().collect::<Vec<_>>().join(" or ");
+          if unit.is_empty() {
+            format!("has no unit, expected {}", expected)
+          } else {
+            format!("has the unknown unit `{}`, expected {}", unit, expected)
+          }
+        })?;
+        range.check(n, unit)
+      }
     }
   }
+}
 
-  fn validate_year(&self, value: &str, min_year: u16, max_year: u16) -> bool {
-    if value.len() != 4 {
-      false
-    } else {
-      value
-        .parse::<u16>()
-        .map(|year| year >= min_year && year <= max_year)
-        .unwrap_or(false)
+/// Only plain decimal digits, without a sign.
+fn parse_integer(value: &str) -> Option<i64> {
+  if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) {
+    value.parse().ok()
+  } else {
+    None
+  }
+}
+
+/// Inclusive bounds, a missing one doesn't restrict the value.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Range {
+  pub min: Option<i64>,
+  pub max: Option<i64>,
+}
+
+impl Range {
+  pub fn new(min: Option<i64>, max: Option<i64>) -> Range {
+    Range { min, max }
+  }
+
+  pub fn contains(&self, n: i64) -> bool {
+    self.check(n, "").is_ok()
+  }
+
+  fn check(&self, n: i64, unit: &str) -> Result<(), String> {
+    match (self.min, self.max) {
+      (Some(min), _) if n < min => Err(format!("is below {}{}", min, unit)),
+      (_, Some(max)) if n > max => Err(format!("exceeds {}{}", max, unit)),
+      _ => Ok(()),
     }
   }
+}
 
-  fn validate_height(&self, value: &str) -> bool {
-    if let Ok(height) = value[..value.len() - 2].parse::<u32>() {
-      match &value[value.len() - 2..] {
-        "cm" => height >= 150 && height <= 193,
-        "in" => height >= 59 && height <= 76,
-        _ => false,
+/// A regular expression that has to match a whole value.
+#[derive(Debug)]
+pub struct Pattern {
+  source: String,
+  regex: Regex,
+}
+
+impl<'de> Deserialize<'de> for Pattern {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Pattern, D::Error> {
+    let source = String::deserialize(deserializer)?;
+    let regex = Regex::new(&format!("^(?:{})$", source)).map_err(de::Error::custom)?;
+    Ok(Pattern { source, regex })
+  }
+}
+
+#[derive(Debug)]
+pub struct Passport {
+  fields: HashMap<String, String>,
+  malformed: Vec<ParseError>,
+}
+
+impl Passport {
+  /// Parses the fields of a passport, which has to be a slice of `input`.
+  /// Tokens that aren't a field and repeated fields are kept as errors instead of failing, a
+  /// repeated field keeps its first value. A passport with errors is invalid in both parts.
+  pub fn parse(input: &str, block: &str) -> Passport {
+    let mut fields = HashMap::<String, String>::new();
+    let mut malformed = Vec::new();
+
+    for token in block.split_whitespace() {
+      match token.split_once(':') {
+        Some((key, value)) if !key.is_empty() => {
+          if fields.contains_key(key) {
+            malformed.push(ParseError::new(DAY, input, token, "repeated field"));
+          } else {
+            fields.insert(key.to_string(), value.to_string());
+          }
+        }
+        _ => malformed.push(ParseError::new(
+          DAY,
+          input,
+          token,
+          "expected `<key>:<value>`",
+        )),
       }
-    } else {
-      false
+    }
+    Passport { fields, malformed }
+  }
+
+  pub fn get(&self, key: &str) -> Option<&str> {
+    self.fields.get(key).map(String::as_str)
+  }
+
+  /// The tokens that couldn't be parsed as a field.
+  pub fn malformed(&self) -> &[ParseError] {
+    &self.malformed
+  }
+
+  pub fn has_required_fields(&self, schema: &Schema) -> bool {
+    schema
+      .required_fields()
+      .all(|key| self.fields.contains_key(key))
+  }
+
+  /// Whether all required fields are present, all fields of the schema have valid values and
+  /// nothing is malformed. Fields the schema doesn't know are ignored.
+  pub fn is_valid(&self, schema: &Schema) -> bool {
+    self.malformed.is_empty()
+      && schema
+        .fields
+        .iter()
+        .all(|(key, rule)| match self.fields.get(key) {
+          Some(value) => rule.kind.is_valid(value),
+          None => !rule.required,
+        })
+  }
+
+  /// Lists everything that is wrong with the passport according to `schema`.
+  pub fn report(&self, schema: &Schema) -> PassportReport {
+    let missing = schema
+      .required_fields()
+      .filter(|key| !self.fields.contains_key(*key))
+      .map(str::to_string)
+      .collect();
+    let invalid = schema
+      .fields
+      .iter()
+      .filter_map(|(key, rule)| {
+        let value = self.fields.get(key)?;
+        let reason = rule.kind.check(value).err()?;
+        Some(InvalidField {
+          field: key.clone(),
+          value: value.clone(),
+          reason,
+        })
+      })
+      .collect();
+    let mut unknown = self
+      .fields
+      .keys()
+      .filter(|key| !schema.fields.contains_key(*key))
+      .cloned()
+      .collect::<Vec<_>>();
+    unknown.sort();
+    PassportReport {
+      missing,
+      invalid,
+      unknown,
+      malformed: self.malformed.iter().map(ParseError::to_string).collect(),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InvalidField {
+  pub field: String,
+  pub value: String,
+  pub reason: String,
+}
+
+impl fmt::Display for InvalidField {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{} {} {}", self.field, self.value, self.reason)
+  }
+}
+
+/// The missing and invalid fields of a passport, the fields the schema doesn't know and the
+/// tokens that aren't fields. Unknown fields don't make a passport invalid.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct PassportReport {
+  pub missing: Vec<String>,
+  pub invalid: Vec<InvalidField>,
+  pub unknown: Vec<String>,
+  pub malformed: Vec<String>,
+}
+
+impl PassportReport {
+  pub fn is_valid(&self) -> bool {
+    self.missing.is_empty() && self.invalid.is_empty() && self.malformed.is_empty()
+  }
+}
+
+#[derive(Serialize)]
+struct ReportEntry<'a> {
+  passport: usize,
+  valid: bool,
+  #[serde(flatten)]
+  report: &'a PassportReport,
+}
+
+/// The reports of a batch of passports, numbered from 0 in the order of the input.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ValidationReport {
+  pub passports: Vec<PassportReport>,
+}
+
+impl ValidationReport {
+  pub fn new(passports: &[Passport], schema: &Schema) -> ValidationReport {
+    ValidationReport {
+      passports: passports.iter().map(|p| p.report(schema)).collect(),
     }
   }
 
-  fn validate_hair_color(&self, value: &str) -> bool {
-    HAIR_COLOR_REGEX.is_match(value)
+  pub fn valid_count(&self) -> usize {
+    self.passports.iter().filter(|r| r.is_valid()).count()
   }
 
-  fn validate_eye_color(&self, value: &str) -> bool {
-    match value {
-      "amb" | "blu" | "brn" | "gry" | "grn" | "hzl" | "oth" => true,
-      _ => false,
+  fn entries(&self) -> impl Iterator<Item = ReportEntry<'_>> {
+    self
+      .passports
+      .iter()
+      .enumerate()
+      .map(|(passport, report)| ReportEntry {
+        passport,
+        valid: report.is_valid(),
+        report,
+      })
+  }
+
+  pub fn to_json(&self) -> String {
+    serde_json::to_string_pretty(&self.entries().collect::<Vec<_>>()).unwrap()
+  }
+
+  /// One row per passport, the fields of a column are separated by `; `.
+  pub fn to_csv(&self) -> String {
+    let mut csv = String::from("passport,valid,missing,invalid,unknown,malformed\n");
+    for entry in self.entries() {
+      let invalid = entry
+        .report
+        .invalid
+        .iter()
+        .map(InvalidField::to_string)
+        .collect::<Vec<_>>();
+      let row = [
+        entry.passport.to_string(),
+        entry.valid.to_string(),
+        csv_field(&entry.report.missing.join("; ")),
+        csv_field(&invalid.join("; ")),
+        csv_field(&entry.report.unknown.join("; ")),
+        csv_field(&entry.report.malformed.join("; ")),
+      ];
+      csv.push_str(&row.join(","));
+      csv.push('\n');
     }
+    csv
   }
+}
 
-  fn validate_passport_id(&self, value: &str) -> bool {
-    value.len() == 9 && value.chars().all(|c| c.is_numeric())
+/// Quotes a CSV field if it contains a separator, quote or line break.
+fn csv_field(field: &str) -> String {
+  if field.contains([',', '"', '\n', '\r']) {
+    format!("\"{}\"", field.replace('"', "\"\""))
+  } else {
+    field.to_string()
   }
 }
 
 #[aoc_generator(day4)]
-fn input_generator(input: &str) -> Vec<Passport> {
-  let re = Regex::new(r" |\n").unwrap();
-
-  input
-    .split("\n\n")
-    .map(|block| {
-      let mut fields = HashMap::<String, String>::new();
-
-      for field in re.split(block) {
-        let key_value = field.split(":").collect::<Vec<_>>();
-        let key = key_value[0];
-        let value = key_value[1];
-        fields.insert(key.to_string(), value.to_string());
-      }
-      Passport { fields }
-    })
-    .collect()
+pub fn input_generator(input: &str) -> Result<Vec<Passport>, ParseError> {
+  // malformed fields are kept in the passports, so parsing never fails
+  let passports = blocks(input)
+    .map(|block| Passport::parse(input, block))
+    .collect();
+  Ok(passports)
 }
 
 #[aoc(day4, part1)]
-fn solve_part1(passports: &[Passport]) -> usize {
-  count_passports(passports, |p| p.has_all_mandatory_fields())
+pub fn solve_part1(passports: &[Passport]) -> usize {
+  count_passports(passports, |p| {
+    p.malformed().is_empty() && p.has_required_fields(Schema::default_schema())
+  })
 }
 
 #[aoc(day4, part2)]
-fn solve_part2(passports: &[Passport]) -> usize {
-  count_passports(passports, |p| p.is_valid())
+pub fn solve_part2(passports: &[Passport]) -> usize {
+  count_passports(passports, |p| p.is_valid(Schema::default_schema()))
 }
 
 fn count_passports<F>(passports: &[Passport], predicate: F) -> usize
@@ -118,3 +406,200 @@ where
 {
   passports.iter().filter(&predicate).count()
 }
+
+pub struct Day04;
+
+impl Solution for Day04 {
+  const DAY: u8 = DAY;
+
+  type Input = Vec<Passport>;
+
+  fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+    input_generator(input)
+  }
+
+  fn part1(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part1(input).into()
+  }
+
+  fn part2(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part2(input).into()
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::{input_generator, solve_part1, solve_part2, InvalidField, Schema, ValidationReport};
+
+  const VISA_SCHEMA: &str = r#"{
+    "fields": {
+      "vid": { "required": true, "type": "text", "pattern": "V-[0-9]+" },
+      "dur": { "required": true, "type": "measure", "units": { "d": { "min": 1, "max": 90 } } },
+      "cls": { "type": "enum", "values": ["A", "B"] },
+      "iss": { "type": "integer", "digits": 4, "min": 2000 }
+    }
+  }"#;
+
+  #[test]
+  fn test_day04_custom_schema() {
+    let schema = Schema::from_json(VISA_SCHEMA).unwrap();
+    let passports = input_generator(
+      "vid:V-17 dur:30d iss:2019\n\n\
+      vid:V-3 cls:C dur:30d\n\n\
+      vid:XV-3 dur:30d\n\n\
+      vid:V-4 dur:91d\n\n\
+      dur:2d",
+    )
+    .unwrap();
+    let valid = passports
+      .iter()
+      .map(|p| (p.has_required_fields(&schema), p.is_valid(&schema)))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      valid,
+      vec![
+        (true, true),
+        (true, false),
+        (true, false),
+        (true, false),
+        (false, false)
+      ]
+    );
+  }
+
+  #[test]
+  fn test_day04_default_schema() {
+    let schema = Schema::default_schema();
+    assert_eq!(
+      schema.required_fields().collect::<Vec<_>>(),
+      vec!["byr", "ecl", "eyr", "hcl", "hgt", "iyr", "pid"]
+    );
+    let passports = input_generator("hgt:190 hcl:#123abz\n\nhgt:60in hcl:#123abc").unwrap();
+    let rule = |key: &str| &schema.fields[key].kind;
+    assert!(!rule("hgt").is_valid(passports[0].get("hgt").unwrap()));
+    assert!(!rule("hcl").is_valid(passports[0].get("hcl").unwrap()));
+    assert!(rule("hgt").is_valid(passports[1].get("hgt").unwrap()));
+    assert!(rule("hcl").is_valid(passports[1].get("hcl").unwrap()));
+    assert!(!rule("hgt").is_valid("cm"));
+    assert!(!rule("byr").is_valid("+1990"));
+  }
+
+  #[test]
+  fn test_day04_invalid_schema() {
+    assert!(
+      Schema::from_json(r#"{ "fields": { "a": { "type": "text", "pattern": "(" } } }"#).is_err()
+    );
+    assert!(Schema::from_json(r#"{ "fields": { "a": { "type": "colour" } } }"#).is_err());
+    assert!(Schema::from_file("does/not/exist.json").is_err());
+  }
+
+  #[test]
+  fn test_day04_validation_report() {
+    let passports = input_generator(
+      "hgt:190in byr:1919 ecl:amb,blu pid:000000001 iyr:2015 eyr:2025 hcl:#123abc\n\n\
+      hgt:170cm byr:1980 ecl:amb pid:000000001 iyr:2015 hcl:#123abc nick:bob",
+    )
+    .unwrap();
+    let report = ValidationReport::new(&passports, Schema::default_schema());
+    assert_eq!(report.valid_count(), 0);
+    let invalid = report.passports[0]
+      .invalid
+      .iter()
+      .map(InvalidField::to_string)
+      .collect::<Vec<_>>();
+    assert_eq!(
+      invalid,
+      vec![
+        "byr 1919 is below 1920",
+        "ecl amb,blu is not one of amb, blu, brn, gry, grn, hzl, oth",
+        "hgt 190in exceeds 76in"
+      ]
+    );
+    assert_eq!(report.passports[1].missing, vec!["eyr"]);
+    assert_eq!(report.passports[1].unknown, vec!["nick"]);
+    assert!(report.passports[1].invalid.is_empty());
+
+    assert_eq!(
+      report.to_csv(),
+      "passport,valid,missing,invalid,unknown,malformed\n\
+      0,false,,\"byr 1919 is below 1920; ecl amb,blu is not one of amb, blu, brn, gry, grn, hzl, oth; hgt 190in exceeds 76in\",,\n\
+      1,false,eyr,,nick,\n"
+    );
+    let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+    assert_eq!(json[1]["passport"], 1);
+    assert_eq!(json[1]["valid"], false);
+    assert_eq!(json[1]["missing"][0], "eyr");
+    assert_eq!(json[0]["invalid"][2]["reason"], "exceeds 76in");
+  }
+
+  #[test]
+  fn test_day04_check_reasons() {
+    let rule = |key: &str| &Schema::default_schema().fields[key].kind;
+    assert_eq!(
+      rule("hgt").check("190"),
+      Err("has no unit, expected cm or in".to_string())
+    );
+    assert_eq!(
+      rule("hgt").check("190ft"),
+      Err("has the unknown unit `ft`, expected cm or in".to_string())
+    );
+    assert_eq!(
+      rule("hgt").check("in"),
+      Err("doesn't start with a number".to_string())
+    );
+    assert_eq!(
+      rule("pid").check("0123"),
+      Err("doesn't match `[0-9]{9}`".to_string())
+    );
+    assert_eq!(
+      rule("byr").check("02000"),
+      Err("doesn't have 4 digits".to_string())
+    );
+    assert_eq!(
+      rule("byr").check("19x0"),
+      Err("is not a number".to_string())
+    );
+    assert_eq!(rule("cid").check("anything"), Ok(()));
+  }
+
+  #[test]
+  fn test_day04_malformed_fields() {
+    let input = "byr:1990 iyr2015 :x\nhgt:é eyr:2020 byr:1991\n\nhgt:\n";
+    let passports = input_generator(input).unwrap();
+    assert_eq!(passports.len(), 2);
+    let errors = passports[0]
+      .malformed()
+      .iter()
+      .map(|e| (e.line, e.column, e.text.as_str(), e.reason.as_str()))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      errors,
+      vec![
+        (1, 10, "iyr2015", "expected `<key>:<value>`"),
+        (1, 18, ":x", "expected `<key>:<value>`"),
+        (2, 16, "byr:1991", "repeated field")
+      ]
+    );
+    assert_eq!(passports[0].get("byr"), Some("1990"));
+    assert_eq!(passports[0].get("hgt"), Some("é"));
+    let schema = Schema::default_schema();
+    assert!(!passports[0].is_valid(schema));
+    let report = passports[0].report(schema);
+    assert_eq!(report.malformed.len(), 3);
+    assert_eq!(
+      report.invalid[0].to_string(),
+      "hgt é doesn't start with a number"
+    );
+    assert_eq!(passports[1].get("hgt"), Some(""));
+    assert!(passports[1].malformed().is_empty());
+
+    let repeated =
+      "byr:1990 iyr:2015 eyr:2025 hgt:180cm hcl:#123abc ecl:brn pid:012345678 byr:1990";
+    let passports = input_generator(repeated).unwrap();
+    assert_eq!(solve_part1(&passports), 0);
+    assert_eq!(solve_part2(&passports), 0);
+    let passports = input_generator(repeated.trim_end_matches(" byr:1990")).unwrap();
+    assert_eq!(solve_part1(&passports), 1);
+    assert_eq!(solve_part2(&passports), 1);
+  }
+}
@@ -1,50 +1,563 @@
-use std::collections::HashSet;
+use crate::{
+  answer::Answer, diagnostics::Diagnostics, input::lines, solution::Solution, ParseError,
+};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::ops::RangeInclusive;
 
-#[derive(Debug)]
+const DAY: u8 = 5;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Seat {
-  code: String,
-  row: u8,
-  col: u8,
-  id: u16,
+  pub code: String,
+  pub row: u32,
+  pub col: u32,
+  pub id: u32,
 }
 
-impl Seat {
-  fn new(code: String) -> Seat {
-    let row = code.chars().take(7).enumerate().fold(0, |acc, (i, x)| {
-      acc + (1 << (6 - i)) * if x == 'F' { 0 } else { 1 }
-    });
+/// Converts between boarding pass codes and seats by binary space partitioning.
+/// A code has one letter per bit of the row, followed by one per bit of the column, most significant
+/// bit first. The first letter of a pair selects the lower half, the second one the upper half.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BspCodec {
+  row_bits: u32,
+  col_bits: u32,
+  row_letters: [char; 2],
+  col_letters: [char; 2],
+}
 
-    let col = code
-      .chars()
-      .rev()
-      .take(3)
-      .enumerate()
-      .fold(0, |acc, (i, x)| {
-        acc + (1 << i) * if x == 'L' { 0 } else { 1 }
+impl Default for BspCodec {
+  /// The plane of the puzzle with 128 rows of 8 seats, like `FBFBBFFRLR`.
+  fn default() -> BspCodec {
+    BspCodec {
+      row_bits: 7,
+      col_bits: 3,
+      row_letters: ['F', 'B'],
+      col_letters: ['L', 'R'],
+    }
+  }
+}
+
+impl BspCodec {
+  /// Fails if a pair of letters isn't distinct or an id wouldn't fit into 32 bits.
+  pub fn new(
+    row_bits: u32,
+    col_bits: u32,
+    row_letters: [char; 2],
+    col_letters: [char; 2],
+  ) -> Result<BspCodec, CodeError> {
+    let bits = row_bits as u64 + col_bits as u64;
+    if bits > 32 {
+      return Err(CodeError::TooManyBits { bits });
+    }
+    for letters in [row_letters, col_letters] {
+      if letters[0] == letters[1] {
+        return Err(CodeError::SameLetters { letters });
+      }
+    }
+    Ok(BspCodec {
+      row_bits,
+      col_bits,
+      row_letters,
+      col_letters,
+    })
+  }
+
+  pub fn rows(&self) -> u64 {
+    1 << self.row_bits
+  }
+
+  pub fn columns(&self) -> u64 {
+    1 << self.col_bits
+  }
+
+  /// The number of letters of a code.
+  pub fn code_len(&self) -> usize {
+    (self.row_bits + self.col_bits) as usize
+  }
+
+  pub fn max_id(&self) -> u32 {
+    ((1u64 << (self.row_bits + self.col_bits)) - 1) as u32
+  }
+
+  /// Numbers the seats row by row, generalising `row * 8 + col`.
+  pub fn id(&self, row: u32, col: u32) -> u32 {
+    ((row as u64) << self.col_bits | col as u64) as u32
+  }
+
+  pub fn decode(&self, code: &str) -> Result<Seat, CodeError> {
+    let found = code.chars().count();
+    if found != self.code_len() {
+      return Err(CodeError::WrongLength {
+        expected: self.code_len(),
+        found,
       });
+    }
 
-    let id = (row as u16) * 8 + col as u16;
+    let mut id = 0u64;
+    for (position, c) in code.chars().enumerate() {
+      let letters = self.letters(position);
+      let bit = letters
+        .iter()
+        .position(|&l| l == c)
+        .ok_or(CodeError::InvalidLetter {
+          position,
+          found: c,
+          expected: letters,
+        })?;
+      id = id << 1 | bit as u64;
+    }
 
-    Seat { code, row, col, id }
+    Ok(Seat {
+      code: code.to_string(),
+      row: (id >> self.col_bits) as u32,
+      col: (id & (self.columns() - 1)) as u32,
+      id: id as u32,
+    })
+  }
+
+  pub fn encode(&self, id: u32) -> Result<String, CodeError> {
+    if id > self.max_id() {
+      return Err(CodeError::IdOutOfRange {
+        id: id as u64,
+        max: self.max_id(),
+      });
+    }
+    let len = self.code_len();
+    Ok(
+      (0..len)
+        .map(|position| self.letters(position)[(id >> (len - 1 - position)) as usize & 1])
+        .collect(),
+    )
+  }
+
+  pub fn encode_seat(&self, row: u32, col: u32) -> Result<String, CodeError> {
+    if row as u64 >= self.rows() || col as u64 >= self.columns() {
+      return Err(CodeError::IdOutOfRange {
+        id: (row as u64) << self.col_bits | col as u64,
+        max: self.max_id(),
+      });
+    }
+    self.encode(self.id(row, col))
+  }
+
+  fn letters(&self, position: usize) -> [char; 2] {
+    if position < self.row_bits as usize {
+      self.row_letters
+    } else {
+      self.col_letters
+    }
   }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodeError {
+  WrongLength {
+    expected: usize,
+    found: usize,
+  },
+  InvalidLetter {
+    position: usize,
+    found: char,
+    expected: [char; 2],
+  },
+  IdOutOfRange {
+    id: u64,
+    max: u32,
+  },
+  /// A codec whose ids would have more than 32 bits.
+  TooManyBits {
+    bits: u64,
+  },
+  /// A codec that uses the same letter for the lower and the upper half.
+  SameLetters {
+    letters: [char; 2],
+  },
+}
+
+impl fmt::Display for CodeError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      CodeError::WrongLength { expected, .. } => {
+        write!(f, "a boarding pass has {} characters", expected)
+      }
+      CodeError::InvalidLetter {
+        expected: [lower, upper],
+        ..
+      } => write!(f, "expected `{}` or `{}`", lower, upper),
+      CodeError::IdOutOfRange { id, max } => {
+        write!(
+          f,
+          "the seat id {} is larger than the highest id {}",
+          id, max
+        )
+      }
+      CodeError::TooManyBits { bits } => {
+        write!(f, "seat ids have at most 32 bits, not {}", bits)
+      }
+      CodeError::SameLetters {
+        letters: [letter, _],
+      } => write!(f, "the lower and upper half can't both be `{}`", letter),
+    }
+  }
+}
+
+impl Error for CodeError {}
+
 #[aoc_generator(day5)]
-fn generate_input(input: &str) -> Vec<Seat> {
-  input.lines().map(|s| Seat::new(s.to_string())).collect()
+pub fn input_generator(input: &str) -> Result<Vec<Seat>, ParseError> {
+  let codec = BspCodec::default();
+  lines(input)
+    .map(|line| {
+      codec.decode(line).map_err(|error| {
+        let text = match error {
+          CodeError::InvalidLetter { position, .. } => {
+            let (i, c) = line.char_indices().nth(position).unwrap();
+            &line[i..i + c.len_utf8()]
+          }
+          _ => line,
+        };
+        ParseError::new(DAY, input, text, &error.to_string())
+      })
+    })
+    .collect()
+}
+
+/// Where a free seat is relative to the occupied ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+  /// Before the first occupied seat, the front rows don't exist on some planes.
+  Front,
+  /// Between occupied seats.
+  Interior,
+  /// After the last occupied seat.
+  Back,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FreeSeat {
+  pub row: u32,
+  pub col: u32,
+  pub id: u32,
+  pub section: Section,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChartStats {
+  pub capacity: usize,
+  pub occupied: usize,
+  pub free: usize,
+  /// Seats with more than one boarding pass.
+  pub duplicates: usize,
+  /// The runs of consecutive occupied seat ids.
+  pub blocks: Vec<RangeInclusive<u32>>,
+  pub full_rows: usize,
+  pub empty_rows: usize,
+}
+
+impl ChartStats {
+  /// The first of the largest blocks.
+  pub fn largest_block(&self) -> Option<&RangeInclusive<u32>> {
+    self
+      .blocks
+      .iter()
+      .rev()
+      .max_by_key(|block| block.end() - block.start())
+  }
+}
+
+/// The boarding passes of a plane per occupied seat. Only the occupied seats are stored, so
+/// even planes with billions of seats take memory proportional to the boarding passes.
+#[derive(Debug, Clone)]
+pub struct SeatingChart {
+  codec: BspCodec,
+  /// the number of boarding passes per seat id, free seats have no entry
+  passes: BTreeMap<u32, usize>,
+}
+
+impl SeatingChart {
+  /// Seats outside of the plane of `codec` are ignored.
+  pub fn new(codec: &BspCodec, seats: &[Seat]) -> SeatingChart {
+    let mut passes = BTreeMap::new();
+    for seat in seats {
+      if (seat.row as u64) < codec.rows() && (seat.col as u64) < codec.columns() {
+        *passes.entry(codec.id(seat.row, seat.col)).or_insert(0) += 1;
+      }
+    }
+    SeatingChart {
+      codec: codec.clone(),
+      passes,
+    }
+  }
+
+  /// The number of boarding passes per occupied seat id.
+  pub fn passes(&self) -> &BTreeMap<u32, usize> {
+    &self.passes
+  }
+
+  fn count(&self, id: u32) -> usize {
+    self.passes.get(&id).copied().unwrap_or(0)
+  }
+
+  /// The row and column of the seat with `id`.
+  fn position(&self, id: u32) -> (u32, u32) {
+    let columns = self.codec.columns();
+    ((id as u64 / columns) as u32, (id as u64 % columns) as u32)
+  }
+
+  /// One line per row, `.` is a free seat, `#` an occupied one and `2` to `9` the number of
+  /// duplicate boarding passes for a seat, `*` if there are more. The text has one character
+  /// per seat of the plane, so it is only useful for small planes.
+  pub fn render(&self) -> String {
+    (0..=self.codec.max_id())
+      .map(|id| {
+        let seat = match self.count(id) {
+          0 => '.',
+          1 => '#',
+          count @ 2..=9 => std::char::from_digit(count as u32, 10).unwrap_or('*'),
+          _ => '*',
+        };
+        let (row, col) = self.position(id);
+        if col == 0 && row > 0 {
+          format!("\n{}", seat)
+        } else {
+          seat.to_string()
+        }
+      })
+      .collect()
+  }
+
+  fn occupied_range(&self) -> Option<(u32, u32)> {
+    let first = *self.passes.keys().next()?;
+    let last = *self.passes.keys().next_back()?;
+    Some((first, last))
+  }
+
+  /// The free seats in the order of their ids, produced on demand since most seats of a large
+  /// plane are free.
+  pub fn free_seats(&self) -> impl Iterator<Item = FreeSeat> + '_ {
+    let range = self.occupied_range();
+    (0..=self.codec.max_id())
+      .filter(move |id| !self.passes.contains_key(id))
+      .map(move |id| {
+        let section = match range {
+          Some((first, _)) if id < first => Section::Front,
+          Some((_, last)) if id > last => Section::Back,
+          Some(_) => Section::Interior,
+          None => Section::Front,
+        };
+        let (row, col) = self.position(id);
+        FreeSeat {
+          row,
+          col,
+          id,
+          section,
+        }
+      })
+  }
+
+  /// The ids of seats with more than one boarding pass and the number of their passes.
+  pub fn duplicates(&self) -> Vec<(u32, usize)> {
+    self
+      .passes
+      .iter()
+      .filter(|&(_, &count)| count > 1)
+      .map(|(&id, &count)| (id, count))
+      .collect()
+  }
+
+  pub fn stats(&self) -> ChartStats {
+    let mut blocks: Vec<RangeInclusive<u32>> = Vec::new();
+    let mut occupied_per_row = BTreeMap::<u32, u64>::new();
+    for &id in self.passes.keys() {
+      match blocks.last_mut() {
+        Some(block) if *block.end() + 1 == id => *block = *block.start()..=id,
+        _ => blocks.push(id..=id),
+      }
+      *occupied_per_row.entry(self.position(id).0).or_insert(0) += 1;
+    }
+    let capacity = (self.codec.rows() * self.codec.columns()) as usize;
+    let occupied = self.passes.len();
+    ChartStats {
+      capacity,
+      occupied,
+      free: capacity - occupied,
+      duplicates: self.passes.values().filter(|&&count| count > 1).count(),
+      blocks,
+      full_rows: occupied_per_row
+        .values()
+        .filter(|&&count| count == self.codec.columns())
+        .count(),
+      empty_rows: self.codec.rows() as usize - occupied_per_row.len(),
+    }
+  }
 }
 
 #[aoc(day5, part1)]
-fn solve_part1(seats: &[Seat]) -> u16 {
-  seats.iter().map(|s| s.id).max().unwrap()
+pub fn solve_part1(seats: &[Seat]) -> Option<u32> {
+  seats.iter().map(|s| s.id).max()
 }
 
+/// The first free seat between occupied ones, the missing front and back rows don't count.
 #[aoc(day5, part2)]
-fn solve_part2(seats: &[Seat]) -> u16 {
-  let min = seats.iter().map(|s| s.id).min().unwrap();
-  let max = seats.iter().map(|s| s.id).max().unwrap();
+pub fn solve_part2(seats: &[Seat]) -> Option<u32> {
+  SeatingChart::new(&BspCodec::default(), seats)
+    .free_seats()
+    .find(|seat| seat.section == Section::Interior)
+    .map(|seat| seat.id)
+}
+
+pub struct Day05;
+
+impl Solution for Day05 {
+  const DAY: u8 = DAY;
+
+  type Input = Vec<Seat>;
 
-  let id_set = seats.iter().map(|s| s.id).collect::<HashSet<_>>();
+  fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+    input_generator(input)
+  }
+
+  fn part1(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part1(input).into()
+  }
+
+  fn part2(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part2(input).into()
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::{
+    input_generator, solve_part1, solve_part2, BspCodec, CodeError, SeatingChart, Section,
+  };
+
+  #[test]
+  fn test_day05_decode() {
+    let codec = BspCodec::default();
+    let seat = codec.decode("BFFFBBFRRR").unwrap();
+    assert_eq!((seat.row, seat.col, seat.id), (70, 7, 567));
+    assert_eq!(
+      codec.decode("BFFFBBFRR"),
+      Err(CodeError::WrongLength {
+        expected: 10,
+        found: 9
+      })
+    );
+    assert_eq!(
+      codec.decode("BFFFBBFRRF"),
+      Err(CodeError::InvalidLetter {
+        position: 9,
+        found: 'F',
+        expected: ['L', 'R']
+      })
+    );
+
+    let error = input_generator("FBFBBFFRLR\nBFFFBBFRLé").unwrap_err();
+    assert_eq!((error.line, error.column), (2, 10));
+    assert_eq!(error.reason, "expected `L` or `R`");
+  }
+
+  #[test]
+  fn test_day05_encode() {
+    let codec = BspCodec::default();
+    assert_eq!(codec.encode(357).unwrap(), "FBFBBFFRLR");
+    assert_eq!(codec.encode_seat(102, 4).unwrap(), "BBFFBBFRLL");
+    assert_eq!(
+      codec.encode(1024),
+      Err(CodeError::IdOutOfRange {
+        id: 1024,
+        max: 1023
+      })
+    );
+    assert!(codec.encode_seat(3, 8).is_err());
 
-  (min..max + 1).find(|id| !id_set.contains(id)).unwrap()
+    let codec = BspCodec::new(2, 4, ['A', 'Z'], ['0', '1']).unwrap();
+    assert_eq!((codec.rows(), codec.columns()), (4, 16));
+    for id in 0..=codec.max_id() {
+      let seat = codec.decode(&codec.encode(id).unwrap()).unwrap();
+      assert_eq!(seat.id, id);
+      assert_eq!(codec.id(seat.row, seat.col), id);
+    }
+    assert_eq!(codec.decode("ZA0101").unwrap().id, 37);
+
+    let codec = BspCodec::new(16, 16, ['F', 'B'], ['L', 'R']).unwrap();
+    assert_eq!(codec.max_id(), u32::MAX);
+    let seat = codec.decode(&codec.encode(u32::MAX).unwrap()).unwrap();
+    assert_eq!((seat.row, seat.col), (65535, 65535));
+    assert_eq!(
+      BspCodec::new(20, 13, ['F', 'B'], ['L', 'R']),
+      Err(CodeError::TooManyBits { bits: 33 })
+    );
+    assert_eq!(
+      BspCodec::new(7, 3, ['F', 'B'], ['L', 'L'])
+        .unwrap_err()
+        .to_string(),
+      "the lower and upper half can't both be `L`"
+    );
+  }
+
+  #[test]
+  fn test_day05_seating_chart() {
+    let codec = BspCodec::new(2, 2, ['F', 'B'], ['L', 'R']).unwrap();
+    let seats = [5, 6, 8, 9, 9, 11]
+      .iter()
+      .map(|&id| codec.decode(&codec.encode(id).unwrap()).unwrap())
+      .collect::<Vec<_>>();
+    let chart = SeatingChart::new(&codec, &seats);
+    assert_eq!(chart.render(), "....\n.##.\n#2.#\n....");
+    assert_eq!(chart.duplicates(), vec![(9, 2)]);
+
+    let free = chart.free_seats().collect::<Vec<_>>();
+    let ids = |section| {
+      free
+        .iter()
+        .filter(|seat| seat.section == section)
+        .map(|seat| seat.id)
+        .collect::<Vec<_>>()
+    };
+    assert_eq!(ids(Section::Front), vec![0, 1, 2, 3, 4]);
+    assert_eq!(ids(Section::Interior), vec![7, 10]);
+    assert_eq!(ids(Section::Back), vec![12, 13, 14, 15]);
+    assert_eq!((free[6].row, free[6].col), (2, 2));
+
+    let stats = chart.stats();
+    assert_eq!((stats.capacity, stats.occupied, stats.free), (16, 5, 11));
+    assert_eq!(stats.duplicates, 1);
+    assert_eq!(stats.blocks, vec![5..=6, 8..=9, 11..=11]);
+    assert_eq!(stats.largest_block(), Some(&(5..=6)));
+    assert_eq!((stats.full_rows, stats.empty_rows), (0, 2));
+  }
+
+  #[test]
+  fn test_day05_large_plane() {
+    let codec = BspCodec::new(16, 16, ['F', 'B'], ['L', 'R']).unwrap();
+    let seats = [70_000, 70_002, u32::MAX]
+      .iter()
+      .map(|&id| codec.decode(&codec.encode(id).unwrap()).unwrap())
+      .collect::<Vec<_>>();
+    let chart = SeatingChart::new(&codec, &seats);
+    assert_eq!(chart.passes().len(), 3);
+    let interior = chart
+      .free_seats()
+      .find(|seat| seat.section == Section::Interior)
+      .unwrap();
+    assert_eq!((interior.id, interior.row, interior.col), (70_001, 1, 4465));
+
+    let stats = chart.stats();
+    assert_eq!(stats.capacity, 1 << 32);
+    assert_eq!(stats.free, (1 << 32) - 3);
+    assert_eq!(stats.largest_block(), Some(&(70_000..=70_000)));
+    assert_eq!((stats.full_rows, stats.empty_rows), (0, 65534));
+  }
+
+  #[test]
+  fn test_day05_no_seats() {
+    assert_eq!(solve_part1(&[]), None);
+    assert_eq!(solve_part2(&[]), None);
+    let seats = input_generator("FFFFFFFLLL\nFFFFFFFLLR").unwrap();
+    assert_eq!(solve_part2(&seats), None);
+  }
 }
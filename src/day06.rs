@@ -1,42 +1,464 @@
-use std::collections::HashSet;
+use crate::{
+  answer::Answer,
+  diagnostics::Diagnostics,
+  input::{blocks, lines},
+  solution::Solution,
+  ParseError,
+};
+use itertools::Either;
+use std::cell::OnceCell;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+use std::iter::FromIterator;
 
-type Answers = Vec<HashSet<char>>;
+const DAY: u8 = 6;
+
+/// The questions one person answered with yes.
+///
+/// Sets of ASCII letters are a bit mask, so union and intersection are a single instruction.
+/// Other letters fall back to a `BTreeSet`, which is only used if the set contains one of them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnswerSet {
+  /// Bit `c as u32` is set for each letter `c`.
+  Ascii(u128),
+  Wide(BTreeSet<char>),
+}
+
+impl AnswerSet {
+  pub fn len(&self) -> usize {
+    match self {
+      AnswerSet::Ascii(mask) => mask.count_ones() as usize,
+      AnswerSet::Wide(set) => set.len(),
+    }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  pub fn contains(&self, question: char) -> bool {
+    match self {
+      AnswerSet::Ascii(mask) => question.is_ascii() && mask >> question as u32 & 1 == 1,
+      AnswerSet::Wide(set) => set.contains(&question),
+    }
+  }
+
+  /// Returns the questions in ascending order.
+  pub fn iter(&self) -> impl Iterator<Item = char> + '_ {
+    match self {
+      AnswerSet::Ascii(mask) => {
+        let mut mask = *mask;
+        Either::Left(std::iter::from_fn(move || {
+          if mask == 0 {
+            return None;
+          }
+          let question = mask.trailing_zeros();
+          mask &= mask - 1;
+          Some(question as u8 as char)
+        }))
+      }
+      AnswerSet::Wide(set) => Either::Right(set.iter().copied()),
+    }
+  }
+
+  pub fn union(&self, other: &AnswerSet) -> AnswerSet {
+    match (self, other) {
+      (AnswerSet::Ascii(a), AnswerSet::Ascii(b)) => AnswerSet::Ascii(a | b),
+      _ => self.iter().chain(other.iter()).collect(),
+    }
+  }
+
+  pub fn intersection(&self, other: &AnswerSet) -> AnswerSet {
+    match (self, other) {
+      (AnswerSet::Ascii(a), AnswerSet::Ascii(b)) => AnswerSet::Ascii(a & b),
+      _ => self.iter().filter(|&q| other.contains(q)).collect(),
+    }
+  }
+}
+
+impl FromIterator<char> for AnswerSet {
+  fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> AnswerSet {
+    let mut mask = 0u128;
+    let mut iter = iter.into_iter();
+    for question in iter.by_ref() {
+      if question.is_ascii() {
+        mask |= 1 << question as u32;
+      } else {
+        let mut set = AnswerSet::Ascii(mask).iter().collect::<BTreeSet<_>>();
+        set.insert(question);
+        set.extend(iter);
+        return AnswerSet::Wide(set);
+      }
+    }
+    AnswerSet::Ascii(mask)
+  }
+}
+
+type Answers = Vec<AnswerSet>;
 
 #[aoc_generator(day6)]
-pub fn generate_input(input: &str) -> Vec<Answers> {
-  input
-    .split("\n\n")
-    .map(|answers| answers.split("\n").map(|a| a.chars().collect()).collect())
+pub fn input_generator(input: &str) -> Result<Vec<Answers>, ParseError> {
+  blocks(input)
+    .map(|answers| {
+      lines(answers)
+        .map(|a| {
+          a.char_indices()
+            .map(|(i, c)| {
+              if c.is_alphabetic() {
+                Ok(c)
+              } else {
+                Err(ParseError::new(
+                  DAY,
+                  input,
+                  &a[i..i + c.len_utf8()],
+                  "answers are letters",
+                ))
+              }
+            })
+            .collect()
+        })
+        .collect()
+    })
     .collect()
 }
 
+/// How many people of a group have to answer a question with yes for it to count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quorum {
+  AtLeast(usize),
+  /// At least this percentage of the group, rounded up to whole people.
+  Percent(u32),
+}
+
+impl Quorum {
+  /// The number of people needed in a group of `people`, at least 1.
+  pub fn threshold(&self, people: usize) -> usize {
+    let threshold = match *self {
+      Quorum::AtLeast(k) => k,
+      Quorum::Percent(p) => (people * p as usize).div_ceil(100),
+    };
+    threshold.max(1)
+  }
+}
+
+/// The questions anyone in a group answered.
+pub fn anyone(answers: &Answers) -> AnswerSet {
+  answers
+    .iter()
+    .fold(AnswerSet::Ascii(0), |acc, person| acc.union(person))
+}
+
+/// The questions everyone in a group answered, none for an empty group.
+pub fn everyone(answers: &Answers) -> AnswerSet {
+  match answers.split_first() {
+    Some((first, rest)) => rest
+      .iter()
+      .fold(first.clone(), |acc, person| acc.intersection(person)),
+    None => AnswerSet::Ascii(0),
+  }
+}
+
+/// The sum of the number of questions that reach the quorum in each group. A quorum of one
+/// person is the union of the answers and a quorum of the whole group their intersection, only
+/// the quorums in between count the people per question.
+pub fn quorum_count(groups: &[Answers], quorum: Quorum) -> usize {
+  groups
+    .iter()
+    .map(|answers| match quorum.threshold(answers.len()) {
+      1 => anyone(answers).len(),
+      threshold if threshold == answers.len() => everyone(answers).len(),
+      _ => GroupStats::new(answers).questions(quorum).len(),
+    })
+    .sum()
+}
+
+/// The number of people per question, counted from the answers of a group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct QuestionCounts {
+  /// indexed like the bits of `AnswerSet::Ascii`
+  ascii: Vec<usize>,
+  wide: BTreeMap<char, usize>,
+}
+
+impl QuestionCounts {
+  fn new(answers: &Answers) -> QuestionCounts {
+    let mut counts = QuestionCounts {
+      ascii: vec![0; 128],
+      wide: BTreeMap::new(),
+    };
+    for question in answers.iter().flat_map(AnswerSet::iter) {
+      if question.is_ascii() {
+        counts.ascii[question as usize] += 1;
+      } else {
+        *counts.wide.entry(question).or_insert(0) += 1;
+      }
+    }
+    counts
+  }
+
+  fn get(&self, question: char) -> usize {
+    if question.is_ascii() {
+      self.ascii[question as usize]
+    } else {
+      self.wide.get(&question).copied().unwrap_or(0)
+    }
+  }
+}
+
+/// The questions a group answered and the number of people who answered each of them.
+///
+/// The union and intersection of the answers are computed right away, the people per question
+/// are only counted when they are needed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupStats {
+  answers: Answers,
+  anyone: AnswerSet,
+  everyone: AnswerSet,
+  counts: OnceCell<QuestionCounts>,
+}
+
+impl GroupStats {
+  pub fn new(answers: &Answers) -> GroupStats {
+    GroupStats {
+      answers: answers.clone(),
+      anyone: anyone(answers),
+      everyone: everyone(answers),
+      counts: OnceCell::new(),
+    }
+  }
+
+  pub fn people(&self) -> usize {
+    self.answers.len()
+  }
+
+  /// The questions anyone in the group answered.
+  pub fn anyone(&self) -> &AnswerSet {
+    &self.anyone
+  }
+
+  /// The questions everyone in the group answered, none for an empty group.
+  pub fn everyone(&self) -> &AnswerSet {
+    &self.everyone
+  }
+
+  fn question_counts(&self) -> &QuestionCounts {
+    self
+      .counts
+      .get_or_init(|| QuestionCounts::new(&self.answers))
+  }
+
+  pub fn count(&self, question: char) -> usize {
+    self.question_counts().get(question)
+  }
+
+  /// The questions answered by anyone and how many people answered them, in ascending order.
+  pub fn counts(&self) -> impl Iterator<Item = (char, usize)> + '_ {
+    let counts = self.question_counts();
+    self
+      .anyone
+      .iter()
+      .map(move |question| (question, counts.get(question)))
+  }
+
+  pub fn questions(&self, quorum: Quorum) -> AnswerSet {
+    match quorum.threshold(self.people()) {
+      1 => self.anyone.clone(),
+      threshold if threshold == self.people() => self.everyone.clone(),
+      threshold => self
+        .counts()
+        .filter(|&(_, count)| count >= threshold)
+        .map(|(question, _)| question)
+        .collect(),
+    }
+  }
+
+  /// The number of questions answered by exactly `k` people at index `k`, from 0 to the size of
+  /// the group. Questions nobody answered aren't known, so the first entry is always 0.
+  pub fn histogram(&self) -> Vec<usize> {
+    let mut histogram = vec![0; self.people() + 1];
+    for (_, count) in self.counts() {
+      histogram[count] += 1;
+    }
+    histogram
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SurveyStats {
+  groups: Vec<GroupStats>,
+}
+
+impl SurveyStats {
+  pub fn new(answers: &[Answers]) -> SurveyStats {
+    SurveyStats {
+      groups: answers.iter().map(GroupStats::new).collect(),
+    }
+  }
+
+  pub fn groups(&self) -> &[GroupStats] {
+    &self.groups
+  }
+
+  /// The sum of the number of questions that reach the quorum in each group.
+  pub fn quorum_count(&self, quorum: Quorum) -> usize {
+    self
+      .groups
+      .iter()
+      .map(|group| group.questions(quorum).len())
+      .sum()
+  }
+
+  /// All questions with the number of people who answered them over all groups, the most
+  /// popular first.
+  pub fn popularity(&self) -> Vec<(char, usize)> {
+    let mut totals = BTreeMap::new();
+    for group in &self.groups {
+      for (question, count) in group.counts() {
+        *totals.entry(question).or_insert(0) += count;
+      }
+    }
+    let mut ranking = totals.into_iter().collect::<Vec<_>>();
+    ranking.sort_by_key(|&(_, count)| Reverse(count));
+    ranking
+  }
+}
+
+/// Questions anyone in a group answered.
 #[aoc(day6, part1)]
 pub fn solve_part1(answers: &[Answers]) -> usize {
-  count_answers(answers, |acc, p_answers| {
-    acc.union(p_answers).cloned().collect()
-  })
+  quorum_count(answers, Quorum::AtLeast(1))
 }
 
+/// Questions everyone in a group answered.
 #[aoc(day6, part2)]
 pub fn solve_part2(answers: &[Answers]) -> usize {
-  count_answers(answers, |acc, p_answers| {
-    acc.intersection(p_answers).cloned().collect()
-  })
+  quorum_count(answers, Quorum::Percent(100))
 }
 
-fn count_answers<F>(answers: &[Answers], combinator: F) -> usize
-where
-  F: Fn(&HashSet<char>, &HashSet<char>) -> HashSet<char>,
-{
-  answers
-    .iter()
-    .map(|answers| {
-      let init: HashSet<char> = answers.first().unwrap().clone();
-      answers
+pub struct Day06;
+
+impl Solution for Day06 {
+  const DAY: u8 = DAY;
+
+  type Input = Vec<Answers>;
+
+  fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+    input_generator(input)
+  }
+
+  fn part1(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part1(input).into()
+  }
+
+  fn part2(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part2(input).into()
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::{
+    input_generator, quorum_count, solve_part1, solve_part2, AnswerSet, GroupStats, Quorum,
+    SurveyStats,
+  };
+  use std::collections::HashSet;
+
+  const TEST_INPUT: &str = "abc\n\na\nb\nc\n\nab\nac\n\na\na\na\na\n\nb";
+
+  #[test]
+  fn test_day06_quorum() {
+    let answers = input_generator(TEST_INPUT).unwrap();
+    assert_eq!(solve_part1(&answers), 11);
+    assert_eq!(solve_part2(&answers), 6);
+
+    let stats = SurveyStats::new(&answers);
+    assert_eq!(
+      stats.quorum_count(Quorum::AtLeast(1)),
+      solve_part1(&answers)
+    );
+    assert_eq!(
+      stats.quorum_count(Quorum::Percent(100)),
+      solve_part2(&answers)
+    );
+    assert_eq!(stats.quorum_count(Quorum::AtLeast(2)), 2);
+    assert_eq!(quorum_count(&answers, Quorum::AtLeast(2)), 2);
+    assert_eq!(stats.quorum_count(Quorum::Percent(50)), 8);
+    assert_eq!(stats.quorum_count(Quorum::Percent(0)), 11);
+    assert_eq!(
+      stats.groups()[2]
+        .questions(Quorum::Percent(51))
         .iter()
-        .skip(1)
-        .fold(init, |acc, p_answers| combinator(&acc, p_answers))
-        .len()
-    })
-    .sum()
+        .collect::<String>(),
+      "a"
+    );
+    assert_eq!(Quorum::Percent(75).threshold(3), 3);
+    assert_eq!(Quorum::Percent(75).threshold(4), 3);
+  }
+
+  #[test]
+  fn test_day06_histogram_and_popularity() {
+    let stats = SurveyStats::new(&input_generator(TEST_INPUT).unwrap());
+    let group = &stats.groups()[2];
+    assert_eq!(
+      (group.people(), group.count('a'), group.count('z')),
+      (2, 2, 0)
+    );
+    assert_eq!(group.histogram(), vec![0, 2, 1]);
+    assert_eq!(stats.groups()[3].histogram(), vec![0, 0, 0, 0, 1]);
+    assert_eq!(stats.popularity(), vec![('a', 8), ('b', 4), ('c', 3)]);
+  }
+
+  #[test]
+  fn test_day06_answer_sets() {
+    let set = "cAz".chars().collect::<AnswerSet>();
+    assert!(matches!(set, AnswerSet::Ascii(_)));
+    assert_eq!(set.iter().collect::<String>(), "Acz");
+    assert!(set.contains('z') && !set.contains('b') && !set.contains('ä'));
+
+    let wide = "zäa".chars().collect::<AnswerSet>();
+    assert!(matches!(wide, AnswerSet::Wide(_)));
+    assert_eq!(wide.iter().collect::<String>(), "azä");
+    assert_eq!(set.union(&wide).iter().collect::<String>(), "Aaczä");
+    // the intersection has no wide letters anymore and compares equal to a mask
+    assert_eq!(set.intersection(&wide), AnswerSet::Ascii(1 << 'z' as u32));
+  }
+
+  #[test]
+  fn test_day06_matches_hash_sets() {
+    let input = "abc\nbcä\nçbcä\n\nxyz\nXYZ\n\nöü\nüö\n\nq";
+    let answers = input_generator(input).unwrap();
+    let groups = input
+      .split("\n\n")
+      .map(|group| {
+        group
+          .lines()
+          .map(|person| person.chars().collect::<HashSet<_>>())
+          .collect::<Vec<_>>()
+      })
+      .collect::<Vec<_>>();
+    for (group, sets) in answers.iter().zip(&groups) {
+      let union = sets.iter().flatten().copied().collect::<HashSet<_>>();
+      let intersection = union
+        .iter()
+        .copied()
+        .filter(|q| sets.iter().all(|set| set.contains(q)))
+        .collect::<HashSet<_>>();
+      let stats = GroupStats::new(group);
+      assert_eq!(stats.anyone().iter().collect::<HashSet<_>>(), union);
+      assert_eq!(
+        stats.everyone().iter().collect::<HashSet<_>>(),
+        intersection
+      );
+      for (question, count) in stats.counts() {
+        let people = sets.iter().filter(|set| set.contains(&question)).count();
+        assert_eq!(count, people);
+      }
+    }
+    assert_eq!(solve_part1(&answers), 14);
+    assert_eq!(solve_part2(&answers), 5);
+    assert_eq!(GroupStats::new(&vec![]).everyone(), &AnswerSet::Ascii(0));
+    let stats = SurveyStats::new(&answers);
+    assert_eq!(stats.quorum_count(Quorum::AtLeast(2)), 5);
+    assert_eq!(quorum_count(&answers, Quorum::AtLeast(2)), 5);
+  }
 }
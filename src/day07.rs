@@ -1,82 +1,376 @@
+use crate::{
+  answer::Answer, diagnostics::Diagnostics, input::lines, parse_at, solution::Solution, ParseError,
+};
 use std::collections::HashMap;
-use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
 
-type Bags = HashMap<String, Vec<(usize, String)>>;
+const DAY: u8 = 7;
+
+const TARGET: &str = "shiny gold";
+
+/// The rules which bags contain which other bags, as a graph between bag colours with edges in
+/// both directions.
+#[derive(Debug, Clone)]
+pub struct BagGraph {
+  colors: Vec<String>,
+  index: HashMap<String, usize>,
+  /// The count and bag of each bag directly inside of a bag.
+  contents: Vec<Vec<(usize, usize)>>,
+  /// The bags that directly contain a bag.
+  containers: Vec<Vec<usize>>,
+  /// The number of bags inside of each bag, computed once in topological order.
+  inside: Vec<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BagError {
+  UnknownColor(String),
+  DuplicateRule(String),
+  /// Bags that contain each other, each one contains the next one and the last one the first.
+  Cycle(Vec<String>),
+}
+
+impl fmt::Display for BagError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      BagError::UnknownColor(color) => write!(f, "there is no rule for {} bags", color),
+      BagError::DuplicateRule(color) => write!(f, "there are two rules for {} bags", color),
+      BagError::Cycle(colors) => write!(
+        f,
+        "the bags contain each other: {} -> {}",
+        colors.join(" -> "),
+        colors[0]
+      ),
+    }
+  }
+}
+
+impl Error for BagError {}
+
+impl BagGraph {
+  /// Creates the graph from the contents of each bag colour, which all need a rule.
+  pub fn new<I>(rules: I) -> Result<BagGraph, BagError>
+  where
+    I: IntoIterator<Item = (String, Vec<(usize, String)>)>,
+  {
+    let rules = rules.into_iter().collect::<Vec<_>>();
+    let mut index = HashMap::with_capacity(rules.len());
+    for (bag, (color, _)) in rules.iter().enumerate() {
+      if index.insert(color.clone(), bag).is_some() {
+        return Err(BagError::DuplicateRule(color.clone()));
+      }
+    }
+
+    let mut contents = Vec::with_capacity(rules.len());
+    let mut containers = vec![Vec::new(); rules.len()];
+    for (bag, (_, inner)) in rules.iter().enumerate() {
+      let inner = inner
+        .iter()
+        .map(|(count, color)| {
+          let inner = *index
+            .get(color)
+            .ok_or_else(|| BagError::UnknownColor(color.clone()))?;
+          containers[inner].push(bag);
+          Ok((*count, inner))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+      contents.push(inner);
+    }
+
+    let mut graph = BagGraph {
+      colors: rules.into_iter().map(|(color, _)| color).collect(),
+      index,
+      contents,
+      containers,
+      inside: Vec::new(),
+    };
+    let mut inside = vec![0u64; graph.colors.len()];
+    for bag in graph.topological_order()? {
+      inside[bag] = graph.contents[bag]
+        .iter()
+        .map(|&(count, inner)| (count as u64).saturating_mul(inside[inner].saturating_add(1)))
+        .fold(0, u64::saturating_add);
+    }
+    graph.inside = inside;
+    Ok(graph)
+  }
+
+  /// Returns the bags ordered so that every bag comes after all bags inside of it.
+  fn topological_order(&self) -> Result<Vec<usize>, BagError> {
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    enum State {
+      New,
+      Open,
+      Done,
+    }
+
+    let mut state = vec![State::New; self.colors.len()];
+    let mut order = Vec::with_capacity(self.colors.len());
+    for root in 0..self.colors.len() {
+      if state[root] != State::New {
+        continue;
+      }
+      // the open bags from the root and the index of the next bag inside of each to visit
+      let mut path = vec![(root, 0)];
+      state[root] = State::Open;
+      while let Some((bag, next)) = path.last_mut() {
+        let bag = *bag;
+        if let Some(&(_, inner)) = self.contents[bag].get(*next) {
+          *next += 1;
+          match state[inner] {
+            State::New => {
+              state[inner] = State::Open;
+              path.push((inner, 0));
+            }
+            State::Open => {
+              let start = path.iter().position(|&(b, _)| b == inner).unwrap_or(0);
+              return Err(BagError::Cycle(
+                path[start..]
+                  .iter()
+                  .map(|&(b, _)| self.colors[b].clone())
+                  .collect(),
+              ));
+            }
+            State::Done => {}
+          }
+        } else {
+          state[bag] = State::Done;
+          order.push(bag);
+          path.pop();
+        }
+      }
+    }
+    Ok(order)
+  }
+
+  fn bag(&self, color: &str) -> Result<usize, BagError> {
+    self
+      .index
+      .get(color)
+      .copied()
+      .ok_or_else(|| BagError::UnknownColor(color.to_string()))
+  }
+
+  /// All colours in the order of their rules.
+  pub fn colors(&self) -> impl Iterator<Item = &str> {
+    self.colors.iter().map(String::as_str)
+  }
+
+  /// The bags directly inside of a bag of `color` and their counts.
+  pub fn contents(&self, color: &str) -> Result<Vec<(usize, &str)>, BagError> {
+    Ok(
+      self.contents[self.bag(color)?]
+        .iter()
+        .map(|&(count, inner)| (count, self.colors[inner].as_str()))
+        .collect(),
+    )
+  }
+
+  /// The bags that directly contain a bag of `color`.
+  pub fn containers(&self, color: &str) -> Result<Vec<&str>, BagError> {
+    Ok(
+      self.containers[self.bag(color)?]
+        .iter()
+        .map(|&bag| self.colors[bag].as_str())
+        .collect(),
+    )
+  }
+
+  /// All bags that eventually contain a bag of `color`, nearest first.
+  pub fn all_containers(&self, color: &str) -> Result<Vec<&str>, BagError> {
+    let mut visited = vec![false; self.colors.len()];
+    let mut queue = VecDeque::new();
+    queue.push_back(self.bag(color)?);
+    let mut found = Vec::new();
+    while let Some(bag) = queue.pop_front() {
+      for &container in &self.containers[bag] {
+        if !visited[container] {
+          visited[container] = true;
+          found.push(self.colors[container].as_str());
+          queue.push_back(container);
+        }
+      }
+    }
+    Ok(found)
+  }
+
+  /// The number of bags inside of a bag of `color`, saturating at `u64::MAX`.
+  pub fn count_inside(&self, color: &str) -> Result<u64, BagError> {
+    Ok(self.inside[self.bag(color)?])
+  }
+}
 
 #[aoc_generator(day7)]
-pub fn input_generator(input: &str) -> HashMap<String, Vec<(usize, String)>> {
-  input
-    .lines()
+pub fn input_generator(input: &str) -> Result<BagGraph, ParseError> {
+  let rules = lines(input)
     .map(|line| {
-      let left_right = line.split(" contain ").collect::<Vec<_>>();
-      let bag_color = left_right[0]
-        .split(" ")
-        .take(2)
-        .collect::<Vec<_>>()
-        .join(" ");
-
-      let contains: Vec<(usize, String)> = if left_right[1].starts_with("no") {
+      let (left, right) = line.split_once(" contain ").ok_or_else(|| {
+        ParseError::new(DAY, input, line, "expected `<color> bags contain <bags>`")
+      })?;
+      let bag_color = left.split(' ').take(2).collect::<Vec<_>>().join(" ");
+
+      let contains: Vec<(usize, String)> = if right.starts_with("no") {
         Vec::new()
       } else {
-        left_right[1]
+        right
           .split(", ")
           .map(|s| {
-            let tokens = s.split(" ").collect::<Vec<_>>();
-            (
-              tokens[0].parse::<usize>().unwrap(),
+            let tokens = s.split(' ').collect::<Vec<_>>();
+            if tokens.len() < 3 {
+              return Err(ParseError::new(
+                DAY,
+                input,
+                s,
+                "expected `<count> <color> bags`",
+              ));
+            }
+            Ok((
+              parse_at(DAY, input, tokens[0], "expected a bag count")?,
               tokens[1].to_string() + " " + tokens[2],
-            )
+            ))
           })
-          .collect()
+          .collect::<Result<_, _>>()?
       };
 
-      (bag_color, contains)
+      Ok((bag_color, contains))
     })
-    .collect()
+    .collect::<Result<Vec<_>, _>>()?;
+
+  BagGraph::new(rules).map_err(|error| {
+    let rule = |color: &str| {
+      let start = format!("{} bags ", color);
+      lines(input).filter(move |line| line.starts_with(&start))
+    };
+    let fragment = match &error {
+      BagError::UnknownColor(color) => lines(input)
+        .filter_map(|line| {
+          let (_, right) = line.split_once(" contain ")?;
+          let start = right.find(color.as_str())?;
+          let offset = right.as_ptr() as usize - line.as_ptr() as usize + start;
+          Some(&line[offset..offset + color.len()])
+        })
+        .next(),
+      BagError::DuplicateRule(color) => rule(color).nth(1),
+      BagError::Cycle(colors) => rule(&colors[0]).next(),
+    };
+    ParseError::new(DAY, input, fragment.unwrap_or(input), &error.to_string())
+  })
 }
 
 #[aoc(day7, part1)]
-pub fn solve_part1(bags: &Bags) -> usize {
-  let mut count = 0;
-  for bag in bags.keys() {
-    let mut visited = HashSet::new();
-    if contains_bag(bags, bag, "shiny gold", &mut visited) {
-      count += 1;
-    }
-  }
+pub fn solve_part1(bags: &BagGraph) -> Option<usize> {
+  bags.all_containers(TARGET).map(|bags| bags.len()).ok()
+}
 
-  count
+#[aoc(day7, part2)]
+pub fn solve_part2(bags: &BagGraph) -> Option<u64> {
+  bags.count_inside(TARGET).ok()
 }
 
-fn contains_bag(bags: &Bags, bag: &str, contains: &str, visited: &mut HashSet<String>) -> bool {
-  let bags_contained = bags.get(bag).unwrap();
-
-  if bags_contained.len() == 0 {
-    false
-  } else {
-    bags_contained.iter().any(|(_, bag)| {
-      if bag == contains {
-        true
-      } else if visited.contains(bag) {
-        false
-      } else {
-        visited.insert(bag.to_string());
-        contains_bag(bags, bag, contains, visited)
-      }
-    })
+pub struct Day07;
+
+impl Solution for Day07 {
+  const DAY: u8 = DAY;
+
+  type Input = BagGraph;
+
+  fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+    input_generator(input)
   }
-}
 
-#[aoc(day7, part2)]
-pub fn solve_part2(bags: &Bags) -> usize {
-  count_bags(bags, "shiny gold") - 1
+  fn part1(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part1(input).into()
+  }
+
+  fn part2(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part2(input).into()
+  }
 }
 
-fn count_bags(bags: &Bags, bag: &str) -> usize {
-  let bags_contained = bags.get(bag).unwrap();
+#[cfg(test)]
+mod test {
+  use super::{input_generator, solve_part1, BagError, BagGraph};
+
+  const TEST_INPUT: &str = "light red bags contain 1 bright white bag, 2 muted yellow bags.\n\
+  dark orange bags contain 3 bright white bags, 4 muted yellow bags.\n\
+  bright white bags contain 1 shiny gold bag.\n\
+  muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.\n\
+  shiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.\n\
+  dark olive bags contain 3 faded blue bags, 4 dotted black bags.\n\
+  vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.\n\
+  faded blue bags contain no other bags.\n\
+  dotted black bags contain no other bags.";
+
+  #[test]
+  fn test_day07_any_target() {
+    let bags = input_generator(TEST_INPUT).unwrap();
+    assert_eq!(solve_part1(&bags), Some(4));
+    assert_eq!(bags.count_inside("shiny gold"), Ok(32));
+    assert_eq!(bags.count_inside("dark olive"), Ok(7));
+    assert_eq!(bags.count_inside("faded blue"), Ok(0));
+    assert_eq!(
+      bags.all_containers("dark olive"),
+      Ok(vec![
+        "shiny gold",
+        "bright white",
+        "muted yellow",
+        "light red",
+        "dark orange"
+      ])
+    );
+    assert_eq!(bags.all_containers("light red"), Ok(vec![]));
+    assert_eq!(
+      bags.containers("faded blue"),
+      Ok(vec!["muted yellow", "dark olive", "vibrant plum"])
+    );
+    assert_eq!(
+      bags.contents("light red"),
+      Ok(vec![(1, "bright white"), (2, "muted yellow")])
+    );
+    assert_eq!(
+      bags.count_inside("plaid teal"),
+      Err(BagError::UnknownColor("plaid teal".to_string()))
+    );
+    assert_eq!(solve_part1(&BagGraph::new(vec![]).unwrap()), None);
+  }
+
+  #[test]
+  fn test_day07_invalid_rules() {
+    let error = input_generator(
+      "light red bags contain 1 dark red bag.\n\
+      dark red bags contain 2 pale red bags.\n\
+      pale red bags contain 1 light red bag, 1 faded blue bag.\n\
+      faded blue bags contain no other bags.",
+    )
+    .unwrap_err();
+    assert_eq!((error.line, error.column), (1, 1));
+    assert_eq!(
+      error.reason,
+      "the bags contain each other: light red -> dark red -> pale red -> light red"
+    );
+
+    let error = input_generator("light red bags contain 3 plaid teal bags.").unwrap_err();
+    assert_eq!(
+      (error.line, error.column, error.text.as_str()),
+      (1, 26, "plaid teal")
+    );
 
-  1 + bags_contained
-    .iter()
-    .map(|(count, bag)| count * count_bags(bags, bag))
-    .sum::<usize>()
+    let error = input_generator(
+      "light red bags contain no other bags.\n\
+      light red bags contain no other bags.",
+    )
+    .unwrap_err();
+    assert_eq!(error.line, 2);
+
+    let cycle = BagGraph::new(vec![(
+      "shiny gold".to_string(),
+      vec![(1, "shiny gold".to_string())],
+    )]);
+    assert_eq!(
+      cycle.unwrap_err(),
+      BagError::Cycle(vec!["shiny gold".to_string()])
+    );
+  }
 }
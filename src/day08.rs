@@ -1,7 +1,16 @@
-use crate::NoSolutionErr;
+use crate::{
+  answer::Answer,
+  diagnostics::{Diagnostics, Event, Silent},
+  input::lines,
+  parse_at,
+  solution::Solution,
+  NoSolutionErr, ParseError,
+};
 use std::collections::HashSet;
 use std::str::FromStr;
 
+const DAY: u8 = 8;
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Operation {
   Acc,
@@ -22,34 +31,62 @@ impl FromStr for Operation {
   }
 }
 
+impl Operation {
+  pub fn name(self) -> &'static str {
+    match self {
+      Operation::Acc => "acc",
+      Operation::Jmp => "jmp",
+      Operation::Nop => "nop",
+    }
+  }
+}
+
 type Instruction = (Operation, i32);
 
+/// Writes an instruction the way it appears in the input.
+fn format_instruction((operation, arg): Instruction) -> String {
+  format!("{} {:+}", operation.name(), arg)
+}
+
 type Program = Vec<Instruction>;
 
 #[aoc_generator(day8)]
-pub fn generate_input(input: &str) -> Program {
-  input
-    .lines()
+pub fn input_generator(input: &str) -> Result<Program, ParseError> {
+  let program = lines(input)
     .map(|line| {
-      let tokens = line.split(" ").collect::<Vec<_>>();
-      (
-        Operation::from_str(tokens[0]).unwrap(),
-        tokens[1].parse().unwrap(),
-      )
+      let (op, arg) = line
+        .split_once(' ')
+        .ok_or_else(|| ParseError::new(DAY, input, line, "expected `<operation> <argument>`"))?;
+      Ok((
+        parse_at(DAY, input, op, "expected `acc`, `jmp` or `nop`")?,
+        parse_at(DAY, input, arg, "expected a signed number")?,
+      ))
     })
-    .collect()
+    .collect::<Result<Program, _>>()?;
+  if program.is_empty() {
+    return Err(ParseError::new(DAY, input, input, "the program is empty"));
+  }
+  Ok(program)
 }
 
+/// The accumulator before an instruction runs twice, `None` if the program terminates.
 #[aoc(day8, part1)]
-pub fn solve_part1(program: &Program) -> i32 {
+pub fn solve_part1(program: &Program) -> Option<i32> {
   match execute_program(program) {
-    Err(acc) => acc,
-    _ => panic!("The progam should be stuck in an infinite loop!"),
+    Run::Looped(acc) => Some(acc),
+    _ => None,
   }
 }
 
 #[aoc(day8, part2)]
 pub fn solve_part2(program: &Program) -> Result<i32, NoSolutionErr> {
+  solve_part2_with(program, &Silent)
+}
+
+pub fn solve_part2_with(
+  program: &Program,
+  diagnostics: &dyn Diagnostics,
+) -> Result<i32, NoSolutionErr> {
   let mut program = program.clone();
   let mut change_index = 0;
   while change_index < program.len() {
@@ -69,16 +106,16 @@ pub fn solve_part2(program: &Program) -> Result<i32, NoSolutionErr> {
     program[change_index] = new_instr;
 
     match execute_program(&program) {
-      Ok(acc) => {
-        println!(
-          "Found corrupted instruction on line {}: {:?}\n\
-                     Set to new instruction: {:?}\n\
-                     acc is '{}'",
-          change_index, prev_instr, new_instr, acc
-        );
+      Run::Terminated(acc) => {
+        diagnostics.report(Event::CorruptedInstruction {
+          line: change_index,
+          corrupted: format_instruction(prev_instr),
+          fixed: format_instruction(new_instr),
+          acc,
+        });
         return Ok(acc);
       }
-      Err(_) => {
+      _ => {
         // reset instruction
         program[change_index] = prev_instr;
         change_index += 1;
@@ -88,7 +125,17 @@ pub fn solve_part2(program: &Program) -> Result<i32, NoSolutionErr> {
   Err(NoSolutionErr {})
 }
 
-fn execute_program(program: &Program) -> Result<i32, i32> {
+/// How a program run ends, with the accumulator at that point.
+enum Run {
+  /// The instruction after the last one would run next.
+  Terminated(i32),
+  /// An instruction would run a second time.
+  Looped(i32),
+  /// A jump left the program somewhere else than right after the last instruction.
+  JumpedOut,
+}
+
+fn execute_program(program: &Program) -> Run {
   let mut instr_ptr: usize = 0;
   let mut executed_instructions = HashSet::<usize>::new();
   let mut acc = 0;
@@ -100,11 +147,10 @@ fn execute_program(program: &Program) -> Result<i32, i32> {
         acc += arg;
       }
       (Operation::Jmp, arg) => {
-        let new_instr_ptr = instr_ptr as i32 + arg;
-        if new_instr_ptr < 0 {
-          panic!("Negative Instruction Ptr");
+        match instr_ptr.checked_add_signed(arg as isize) {
+          Some(new_instr_ptr) => instr_ptr = new_instr_ptr,
+          None => return Run::JumpedOut,
         }
-        instr_ptr = new_instr_ptr as usize;
         continue;
       }
       (Operation::Nop, _) => {}
@@ -112,8 +158,46 @@ fn execute_program(program: &Program) -> Result<i32, i32> {
     instr_ptr += 1;
   }
   if instr_ptr == program.len() {
-    Ok(acc)
+    Run::Terminated(acc)
+  } else if instr_ptr > program.len() {
+    Run::JumpedOut
   } else {
-    Err(acc)
+    Run::Looped(acc)
+  }
+}
+
+pub struct Day08;
+
+impl Solution for Day08 {
+  const DAY: u8 = DAY;
+
+  type Input = Program;
+
+  fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+    input_generator(input)
+  }
+
+  fn part1(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part1(input).into()
+  }
+
+  fn part2(&self, input: &Self::Input, diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part2_with(input, diagnostics).into()
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::{input_generator, solve_part1, solve_part2};
+
+  #[test]
+  fn test_day08_programs_without_loop() {
+    let terminates = input_generator("nop +0\nacc +1\njmp +1").unwrap();
+    assert_eq!(solve_part1(&terminates), None);
+    let jumps_out = input_generator("acc +1\njmp -2").unwrap();
+    assert_eq!(solve_part1(&jumps_out), None);
+    assert_eq!(solve_part2(&jumps_out).ok(), Some(1));
+    let jumps_far = input_generator("acc +2\njmp +5\njmp -1").unwrap();
+    assert_eq!(solve_part1(&jumps_far), None);
   }
 }
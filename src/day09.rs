@@ -1,10 +1,18 @@
-use crate::NoSolutionErr;
+use crate::{
+  answer::Answer,
+  diagnostics::{Diagnostics, Event, Silent},
+  input::lines,
+  parse_at,
+  solution::Solution,
+  NoSolutionErr, ParseError,
+};
+
+const DAY: u8 = 9;
 
 #[aoc_generator(day9)]
-pub fn input_generator(input: &str) -> Vec<u64> {
-  input
-    .lines()
-    .map(|line| line.parse::<u64>().unwrap())
+pub fn input_generator(input: &str) -> Result<Vec<u64>, ParseError> {
+  lines(input)
+    .map(|line| parse_at(DAY, input, line, "expected a number"))
     .collect()
 }
 
@@ -44,6 +52,13 @@ fn find_xmas_weakness(numbers: &[u64], preample_length: usize) -> Result<u64, No
 
 #[aoc(day9, part2)]
 pub fn solve_part2(numbers: &[u64]) -> Result<u64, NoSolutionErr> {
+  solve_part2_with(numbers, &Silent)
+}
+
+pub fn solve_part2_with(
+  numbers: &[u64],
+  diagnostics: &dyn Diagnostics,
+) -> Result<u64, NoSolutionErr> {
   let goal = solve_part1(numbers)?;
 
   let mut start = 0;
@@ -51,13 +66,18 @@ pub fn solve_part2(numbers: &[u64]) -> Result<u64, NoSolutionErr> {
 
   for i in 1..numbers.len() {
     while curr_sum > goal && start < i - 1 {
-      curr_sum = curr_sum - numbers[start];
+      curr_sum -= numbers[start];
       start += 1;
     }
 
-    if curr_sum == goal {
-      let range = &numbers[start..i + 1];
-      println!("Found Range: sum({:?}) = {:?}", range, goal);
+    // curr_sum is the sum of numbers[start..i], which needs at least two numbers
+    if curr_sum == goal && i - start >= 2 {
+      let range = &numbers[start..i];
+      diagnostics.report(Event::RangeFound {
+        start,
+        end: i,
+        sum: goal,
+      });
       return Ok(range.iter().min().unwrap() + range.iter().max().unwrap());
     }
 
@@ -66,3 +86,23 @@ pub fn solve_part2(numbers: &[u64]) -> Result<u64, NoSolutionErr> {
 
   Err(NoSolutionErr {})
 }
+
+pub struct Day09;
+
+impl Solution for Day09 {
+  const DAY: u8 = DAY;
+
+  type Input = Vec<u64>;
+
+  fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+    input_generator(input)
+  }
+
+  fn part1(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part1(input).into()
+  }
+
+  fn part2(&self, input: &Self::Input, diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part2_with(input, diagnostics).into()
+  }
+}
@@ -1,16 +1,24 @@
+use crate::{
+  answer::Answer, diagnostics::Diagnostics, input::lines, parse_at, solution::Solution, ParseError,
+};
+
+const DAY: u8 = 10;
+
 #[aoc_generator(day10)]
-pub fn input_generator(input: &str) -> Vec<u32> {
-  let mut adapters: Vec<u32> = input
-    .lines()
-    .map(|line| line.parse::<u32>().unwrap())
-    .collect();
-  adapters.sort();
-  adapters
+pub fn input_generator(input: &str) -> Result<Vec<u32>, ParseError> {
+  let mut adapters: Vec<u32> = lines(input)
+    .map(|line| parse_at(DAY, input, line, "expected an adapter rating"))
+    .collect::<Result<_, _>>()?;
+  if adapters.is_empty() {
+    return Err(ParseError::new(DAY, input, input, "there are no adapters"));
+  }
+  adapters.sort_unstable();
+  Ok(adapters)
 }
 
 #[aoc(day10, part1)]
 // the adapters should already be sorted
-pub fn solve_day1(adapters: &[u32]) -> u32 {
+pub fn solve_part1(adapters: &[u32]) -> u32 {
   let difference_count = get_jolts_difference_count(adapters);
   difference_count[0] * difference_count[2]
 }
@@ -35,12 +43,12 @@ pub fn get_jolts_difference_count(adapters: &[u32]) -> [u32; 3] {
 
 #[aoc(day10, part2)]
 // the adapters should already be sorted
-pub fn solve_day2(adapters: &[u32]) -> u64 {
+pub fn solve_part2(adapters: &[u32]) -> u64 {
   let mut arrangements: Vec<u64> = Vec::new();
 
   for i in 0..adapters.len() as i32 {
     let mut next_arrangements = 0;
-    for j in 1..=3 as i32 {
+    for j in 1..=3 {
       let k = i - j;
 
       if k >= 0 {
@@ -60,10 +68,30 @@ pub fn solve_day2(adapters: &[u32]) -> u64 {
   *arrangements.last().unwrap()
 }
 
+pub struct Day10;
+
+impl Solution for Day10 {
+  const DAY: u8 = DAY;
+
+  type Input = Vec<u32>;
+
+  fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+    input_generator(input)
+  }
+
+  fn part1(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part1(input).into()
+  }
+
+  fn part2(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part2(input).into()
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::get_jolts_difference_count;
-  use super::solve_day2;
+  use super::solve_part2;
 
   lazy_static! {
     static ref TEST_INPUT_SMALL: Vec<u32> = {
@@ -81,33 +109,33 @@ mod tests {
     };
   }
 
-  fn test_solve_day1(adapters: &Vec<u32>, expected: (u32, u32)) {
-    let diff_count = get_jolts_difference_count(&adapters);
+  fn test_solve_part1(adapters: &[u32], expected: (u32, u32)) {
+    let diff_count = get_jolts_difference_count(adapters);
     assert_eq!(diff_count[0], expected.0);
     assert_eq!(diff_count[2], expected.1);
   }
 
-  fn test_solve_day_2(adapters: &Vec<u32>, expected: u64) {
-    assert_eq!(solve_day2(&adapters), expected);
+  fn test_solve_part2(adapters: &[u32], expected: u64) {
+    assert_eq!(solve_part2(adapters), expected);
   }
 
   #[test]
   fn test_day10_part1_small() {
-    test_solve_day1(&TEST_INPUT_SMALL, (7, 5));
+    test_solve_part1(&TEST_INPUT_SMALL, (7, 5));
   }
 
   #[test]
   fn test_day10_part1_larger() {
-    test_solve_day1(&TEST_INPUT_LARGER, (22, 10));
+    test_solve_part1(&TEST_INPUT_LARGER, (22, 10));
   }
 
   #[test]
   fn test_day10_part2_small() {
-    test_solve_day_2(&TEST_INPUT_SMALL, 8);
+    test_solve_part2(&TEST_INPUT_SMALL, 8);
   }
 
   #[test]
   fn test_day10_part2_larger() {
-    test_solve_day_2(&TEST_INPUT_LARGER, 19208);
+    test_solve_part2(&TEST_INPUT_LARGER, 19208);
   }
 }
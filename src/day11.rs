@@ -1,3 +1,14 @@
+use crate::{
+  answer::Answer,
+  automaton::{Automaton, SquareGrid},
+  diagnostics::Diagnostics,
+  grid::Grid,
+  solution::Solution,
+  ParseError,
+};
+
+const DAY: u8 = 11;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SeatState {
   Occupied,
@@ -5,117 +16,58 @@ pub enum SeatState {
   Floor,
 }
 
-#[derive(Clone)]
-pub struct SeatLayout {
-  rows: i32,
-  cols: i32,
-  seats: Vec<SeatState>,
-}
-
-impl SeatLayout {
-  fn get(&self, x: i32, y: i32) -> Option<SeatState> {
-    if x < 0 || y < 0 {
-      None
-    } else {
-      self.seats.get((y * self.cols + x) as usize).copied()
-    }
-  }
-
-  fn set(&mut self, x: usize, y: usize, seat_state: SeatState) {
-    self.seats[y * self.cols as usize + x] = seat_state;
-  }
-
-  fn swap(&mut self, other: &mut SeatLayout) {
-    std::mem::swap(self, other)
-  }
-}
+pub type SeatLayout = Grid<SeatState>;
 
 #[aoc_generator(day11)]
-pub fn input_generator(input: &str) -> SeatLayout {
-  let seats = input
-    .replace("\n", "")
-    .chars()
-    .map(|c| match c {
-      '#' => SeatState::Occupied,
-      'L' => SeatState::Empty,
-      '.' => SeatState::Floor,
-      _ => panic!("Invalid seat char!"),
-    })
-    .collect();
-
-  let cols = input.lines().next().unwrap().chars().count() as i32;
-  let rows = input.lines().count() as i32;
-
-  SeatLayout { rows, cols, seats }
+pub fn input_generator(input: &str) -> Result<SeatLayout, ParseError> {
+  Grid::parse(DAY, input, input, "expected `#`, `L` or `.`", |c| match c {
+    '#' => Some(SeatState::Occupied),
+    'L' => Some(SeatState::Empty),
+    '.' => Some(SeatState::Floor),
+    _ => None,
+  })
 }
 
 #[aoc(day11, part1)]
 pub fn solve_part1(seat_layout: &SeatLayout) -> usize {
-  game_of_seats(seat_layout, 4, 1)
+  game_of_seats(seat_layout, "B0/S0123", 1)
 }
 
 #[aoc(day11, part2)]
 pub fn solve_part2(seat_layout: &SeatLayout) -> usize {
-  game_of_seats(seat_layout, 5, std::u32::MAX)
+  game_of_seats(seat_layout, "B0/S01234", usize::MAX)
+}
+
+/// Seats are cells of an automaton, which are alive when occupied and see the next seats
+/// within `max_sight` in each direction.
+fn game_of_seats(seats: &SeatLayout, rule: &str, max_sight: usize) -> usize {
+  let topology = SquareGrid::new(seats, max_sight, |&seat| seat != SeatState::Floor);
+  let occupied = seats
+    .positions()
+    .filter(|&position| seats[position] == SeatState::Occupied);
+  let mut automaton = Automaton::new(topology, rule.parse().unwrap(), occupied);
+  automaton.run_until_stable();
+  automaton.alive_count()
 }
 
-fn game_of_seats(seats: &SeatLayout, emptying_rule_count: u8, max_sight: u32) -> usize {
-  let mut seats = seats.clone();
-  let mut next_seats = seats.clone();
-  let mut changed = true;
-  let mut occupied_count = 0;
-  while changed {
-    changed = false;
-    occupied_count = 0;
-    for y in 0..seats.rows {
-      for x in 0..seats.cols {
-        let cell = seats.get(x, y).unwrap();
-        match cell {
-          SeatState::Empty => continue,
-          SeatState::Occupied => occupied_count += 1,
-          _ => (),
-        }
-        let mut neighbours: u8 = 0;
-        for dy in -1..=1 as i32 {
-          for dx in -1..=1 as i32 {
-            if dy == 0 && dx == 0 {
-              continue;
-            }
-            let mut nx = x + dx;
-            let mut ny = y + dy;
-            let mut sight = 1;
-            while sight < max_sight {
-              if seats.get(nx, ny) != Some(SeatState::Floor) {
-                break;
-              }
-              nx += dx;
-              ny += dy;
-              sight += 1;
-            }
-
-            if seats.get(nx, ny) == Some(SeatState::Occupied) {
-              neighbours += 1;
-            }
-          }
-        }
-
-        next_seats.set(
-          x as usize,
-          y as usize,
-          match cell {
-            SeatState::Empty if neighbours == 0 => SeatState::Occupied,
-            SeatState::Occupied if neighbours >= emptying_rule_count => SeatState::Empty,
-            _ => cell,
-          },
-        );
-
-        changed |= next_seats.get(x, y).unwrap() != cell;
-      }
-    }
-    seats.swap(&mut next_seats);
+pub struct Day11;
+
+impl Solution for Day11 {
+  const DAY: u8 = DAY;
+
+  type Input = SeatLayout;
+
+  fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+    input_generator(input)
+  }
+
+  fn part1(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part1(input).into()
   }
 
-  occupied_count
+  fn part2(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part2(input).into()
+  }
 }
 
 #[cfg(test)]
@@ -123,7 +75,7 @@ mod tests {
 
   use super::{input_generator, solve_part1, solve_part2};
 
-  const TEST_INPUT: &'static str = "L.LL.LL.LL\n\
+  const TEST_INPUT: &str = "L.LL.LL.LL\n\
     LLLLLLL.LL\n\
     L.L.L..L..\n\
     LLLL.LL.LL\n\
@@ -136,13 +88,13 @@ mod tests {
 
   #[test]
   fn test_day11_part1() {
-    let seat_layout = input_generator(TEST_INPUT);
+    let seat_layout = input_generator(TEST_INPUT).unwrap();
     assert_eq!(solve_part1(&seat_layout), 37);
   }
 
   #[test]
   fn test_day11_part2() {
-    let seat_layout = input_generator(TEST_INPUT);
+    let seat_layout = input_generator(TEST_INPUT).unwrap();
     assert_eq!(solve_part2(&seat_layout), 26);
   }
 }
@@ -1,3 +1,9 @@
+use crate::{
+  answer::Answer, diagnostics::Diagnostics, input::lines, parse_at, solution::Solution, ParseError,
+};
+
+const DAY: u8 = 12;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum NavAction {
   North(i32),
@@ -113,12 +119,17 @@ impl Ship {
 }
 
 #[aoc_generator(day12)]
-pub fn input_generator(input: &str) -> Vec<NavAction> {
-  input
-    .lines()
+pub fn input_generator(input: &str) -> Result<Vec<NavAction>, ParseError> {
+  lines(input)
     .map(|line| {
-      let value = line[1..].parse::<i32>().unwrap();
-      match line.chars().next().unwrap() {
+      let action = line
+        .chars()
+        .next()
+        .ok_or_else(|| ParseError::new(DAY, input, line, "expected a navigation action"))?;
+      let value_str = &line[action.len_utf8()..];
+      let value = parse_at(DAY, input, value_str, "expected a number")?;
+      let invalid_angle = || ParseError::new(DAY, input, value_str, "expected 90, 180 or 270");
+      Ok(match action {
         'N' => NavAction::North(value),
         'S' => NavAction::South(value),
         'E' => NavAction::East(value),
@@ -127,17 +138,24 @@ pub fn input_generator(input: &str) -> Vec<NavAction> {
           90 => NavAction::Left,
           180 => NavAction::TurnAround,
           270 => NavAction::Right,
-          _ => unreachable!(),
+          _ => return Err(invalid_angle()),
         },
         'R' => match value {
           90 => NavAction::Right,
           180 => NavAction::TurnAround,
           270 => NavAction::Left,
-          _ => unreachable!(),
+          _ => return Err(invalid_angle()),
         },
         'F' => NavAction::Forward(value),
-        _ => unreachable!(),
-      }
+        _ => {
+          return Err(ParseError::new(
+            DAY,
+            input,
+            &line[..action.len_utf8()],
+            "expected one of `N`, `S`, `E`, `W`, `L`, `R` or `F`",
+          ))
+        }
+      })
     })
     .collect()
 }
@@ -156,19 +174,39 @@ pub fn solve_part2(nav_actions: &[NavAction]) -> i32 {
   ship.manhattan_distance()
 }
 
+pub struct Day12;
+
+impl Solution for Day12 {
+  const DAY: u8 = DAY;
+
+  type Input = Vec<NavAction>;
+
+  fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+    input_generator(input)
+  }
+
+  fn part1(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part1(input).into()
+  }
+
+  fn part2(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part2(input).into()
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::{input_generator, solve_part1, solve_part2, NavAction, Ship};
 
   #[test]
   fn test_day12_part1() {
-    let actions = input_generator("F10\nN3\nF7\nR90\nF11");
+    let actions = input_generator("F10\nN3\nF7\nR90\nF11").unwrap();
     assert_eq!(solve_part1(&actions), 25);
   }
 
   #[test]
   fn test_day12_part2() {
-    let actions = input_generator("F10\nN3\nF7\nR90\nF11");
+    let actions = input_generator("F10\nN3\nF7\nR90\nF11").unwrap();
     assert_eq!(solve_part2(&actions), 286);
   }
 
@@ -184,7 +222,14 @@ mod tests {
       NavAction::Right,
     ];
 
-    assert_eq!(input_generator(action_str), actions);
+    assert_eq!(input_generator(action_str).unwrap(), actions);
+  }
+
+  #[test]
+  fn test_day12_gen_input_error() {
+    let error = input_generator("F10\nN3\nR45").unwrap_err();
+    assert_eq!((error.day, error.line, error.column), (12, 3, 2));
+    assert_eq!(error.text, "45");
   }
 
   #[test]
@@ -1,3 +1,9 @@
+use crate::{
+  answer::Answer, diagnostics::Diagnostics, input::lines, parse_at, solution::Solution, ParseError,
+};
+
+const DAY: u8 = 13;
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Input {
   earliest_timestamp: i128,
@@ -14,26 +20,34 @@ impl Input {
 }
 
 #[aoc_generator(day13)]
-pub fn input_generator(input: &str) -> Input {
-  let lines = input.lines().collect::<Vec<&str>>();
-  let earliest_timestamp = lines[0].parse::<i128>().unwrap();
-  let busses = lines[1]
-    .split(",")
+pub fn input_generator(input: &str) -> Result<Input, ParseError> {
+  let mut lines = lines(input);
+  let missing_line = || ParseError::new(DAY, input, &input[input.len()..], "expected two lines");
+  let earliest_timestamp = parse_at(
+    DAY,
+    input,
+    lines.next().ok_or_else(missing_line)?,
+    "expected the earliest timestamp",
+  )?;
+  let busses = lines
+    .next()
+    .ok_or_else(missing_line)?
+    .split(',')
     .map(|string| match string {
-      "x" => None,
-      _ => Some(string.parse::<i128>().unwrap()),
+      "x" => Ok(None),
+      _ => parse_at(DAY, input, string, "expected a bus id or `x`").map(Some),
     })
-    .collect();
+    .collect::<Result<_, _>>()?;
 
-  Input {
+  Ok(Input {
     earliest_timestamp,
     busses,
-  }
+  })
 }
 
 #[aoc(day13, part1)]
 pub fn solve_part1(input: &Input) -> i128 {
-  let busses: Vec<i128> = input.busses.iter().filter_map(|x| x.clone()).collect();
+  let busses: Vec<i128> = input.busses.iter().filter_map(|x| *x).collect();
 
   let mut t = input.earliest_timestamp;
   loop {
@@ -93,7 +107,27 @@ fn gcd_extended(a: i128, b: i128, x: &mut i128, y: &mut i128) -> i128 {
   *x = y1 - (b / a) * x1;
   *y = x1;
 
-  return gcd;
+  gcd
+}
+
+pub struct Day13;
+
+impl Solution for Day13 {
+  const DAY: u8 = DAY;
+
+  type Input = Input;
+
+  fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+    input_generator(input)
+  }
+
+  fn part1(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part1(input).into()
+  }
+
+  fn part2(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part2(input).into()
+  }
 }
 
 #[cfg(test)]
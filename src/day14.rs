@@ -1,7 +1,12 @@
+use crate::{
+  answer::Answer, diagnostics::Diagnostics, input::lines, parse_at, solution::Solution, ParseError,
+};
 use regex::Regex;
 use std::collections::HashMap;
 use std::str::FromStr;
 
+const DAY: u8 = 14;
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Instruction {
   SetMask(Mask),
@@ -41,23 +46,38 @@ impl FromStr for Mask {
 }
 
 #[aoc_generator(day14)]
-pub fn input_generator(input: &str) -> Vec<Instruction> {
-  let re_mem = Regex::new(r"mem\[(\d+)\]").unwrap();
+pub fn input_generator(input: &str) -> Result<Vec<Instruction>, ParseError> {
+  let re_mem = Regex::new(r"^mem\[(\d+)\]$").unwrap();
+  let re_mask = Regex::new(r"^[01X]{36}$").unwrap();
 
-  input
-    .lines()
+  lines(input)
     .map(|line| {
-      let left_right: Vec<&str> = line.split(" = ").collect();
-      let left = left_right[0];
-      let right = left_right[1];
+      let (left, right) = line
+        .split_once(" = ")
+        .ok_or_else(|| ParseError::new(DAY, input, line, "expected `<target> = <value>`"))?;
       if left == "mask" {
-        Instruction::SetMask(Mask::from_str(right).unwrap())
-      } else {
-        let captures = re_mem.captures(left).unwrap();
-        Instruction::SetMemory {
-          address: captures[1].parse::<u64>().unwrap(),
-          value: right.parse::<u64>().unwrap(),
+        if !re_mask.is_match(right) {
+          return Err(ParseError::new(
+            DAY,
+            input,
+            right,
+            "expected 36 mask bits of `0`, `1` or `X`",
+          ));
         }
+        Ok(Instruction::SetMask(Mask::from_str(right).unwrap()))
+      } else {
+        let captures = re_mem.captures(left).ok_or_else(|| {
+          ParseError::new(DAY, input, left, "expected `mask` or `mem[<address>]`")
+        })?;
+        Ok(Instruction::SetMemory {
+          address: parse_at(
+            DAY,
+            input,
+            captures.get(1).unwrap().as_str(),
+            "invalid address",
+          )?,
+          value: parse_at(DAY, input, right, "expected a number")?,
+        })
       }
     })
     .collect()
@@ -70,7 +90,7 @@ pub fn solve_part1(instructions: &[Instruction]) -> u64 {
 
   for instr in instructions {
     match instr {
-      Instruction::SetMask(m) => mask = Some(m.clone()),
+      Instruction::SetMask(m) => mask = Some(*m),
       Instruction::SetMemory { address, value } => {
         let mask = mask.unwrap();
         mem.insert(*address, (value | mask.one_mask) & (!mask.zero_mask));
@@ -88,7 +108,7 @@ pub fn solve_part2(instructions: &[Instruction]) -> u64 {
 
   for instr in instructions {
     match instr {
-      Instruction::SetMask(m) => mask = Some(m.clone()),
+      Instruction::SetMask(m) => mask = Some(*m),
       Instruction::SetMemory { address, value } => {
         let mask = mask.unwrap();
         let decoded_addr = address | mask.one_mask;
@@ -129,11 +149,31 @@ fn get_set_bit_positions(mask: u64) -> Vec<u64> {
   positions
 }
 
+pub struct Day14;
+
+impl Solution for Day14 {
+  const DAY: u8 = DAY;
+
+  type Input = Vec<Instruction>;
+
+  fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+    input_generator(input)
+  }
+
+  fn part1(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part1(input).into()
+  }
+
+  fn part2(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part2(input).into()
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::{input_generator, solve_part1, solve_part2, FromStr, Instruction, Mask};
 
-  const TEST_INPUT_PART1: &'static str = "mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X\n\
+  const TEST_INPUT_PART1: &str = "mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X\n\
   mem[8] = 11\n\
   mem[7] = 101\n\
   mem[8] = 0";
@@ -156,12 +196,15 @@ mod tests {
       },
     ];
 
-    assert_eq!(input_generator(TEST_INPUT_PART1), expected);
+    assert_eq!(input_generator(TEST_INPUT_PART1).unwrap(), expected);
   }
 
   #[test]
   fn test_day14_part1() {
-    assert_eq!(solve_part1(&input_generator(TEST_INPUT_PART1)), 165)
+    assert_eq!(
+      solve_part1(&input_generator(TEST_INPUT_PART1).unwrap()),
+      165
+    )
   }
 
   #[test]
@@ -171,6 +214,6 @@ mod tests {
     mask = 00000000000000000000000000000000X0XX\n\
     mem[26] = 1";
 
-    assert_eq!(solve_part2(&input_generator(input)), 208)
+    assert_eq!(solve_part2(&input_generator(input).unwrap()), 208)
   }
 }
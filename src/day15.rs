@@ -1,10 +1,14 @@
+use crate::{answer::Answer, diagnostics::Diagnostics, parse_at, solution::Solution, ParseError};
 use std::collections::HashMap;
 
+const DAY: u8 = 15;
+
 #[aoc_generator(day15)]
-pub fn input_generator(input: &str) -> Vec<usize> {
+pub fn input_generator(input: &str) -> Result<Vec<usize>, ParseError> {
   input
-    .split(",")
-    .map(|s| s.parse::<usize>().unwrap())
+    .trim_end()
+    .split(',')
+    .map(|s| parse_at(DAY, input, s, "expected a starting number"))
     .collect()
 }
 
@@ -49,12 +53,32 @@ fn play_game(start_numbers: &[usize], nth: usize) -> usize {
   last
 }
 
+pub struct Day15;
+
+impl Solution for Day15 {
+  const DAY: u8 = DAY;
+
+  type Input = Vec<usize>;
+
+  fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+    input_generator(input)
+  }
+
+  fn part1(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part1(input).into()
+  }
+
+  fn part2(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part2(input).into()
+  }
+}
+
 #[cfg(test)]
 pub mod test {
   use super::{input_generator, solve_part1};
 
   #[test]
   fn test_day15_part1() {
-    assert_eq!(solve_part1(&input_generator("0,3,6")), 436);
+    assert_eq!(solve_part1(&input_generator("0,3,6").unwrap()), 436);
   }
 }
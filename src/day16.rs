@@ -1,5 +1,15 @@
+use crate::{
+  answer::Answer,
+  diagnostics::Diagnostics,
+  input::{blocks, lines},
+  parse_at,
+  solution::Solution,
+  ParseError,
+};
 use std::collections::HashSet;
 
+const DAY: u8 = 16;
+
 type Ticket = Vec<u64>;
 type Rule = (String, Vec<Range>);
 
@@ -17,46 +27,73 @@ pub struct Range {
 }
 
 #[aoc_generator(day16)]
-pub fn input_generator(input: &str) -> Input {
-  let blocks = input.split("\n\n").collect::<Vec<&str>>();
+pub fn input_generator(input: &str) -> Result<Input, ParseError> {
+  let blocks = blocks(input).collect::<Vec<&str>>();
+  if blocks.len() != 3 {
+    return Err(ParseError::new(
+      DAY,
+      input,
+      input,
+      "expected rules, your ticket and nearby tickets separated by blank lines",
+    ));
+  }
 
-  let rules: Vec<Rule> = blocks[0]
-    .lines()
+  let rules: Vec<Rule> = lines(blocks[0])
     .map(|line| {
-      let key_value = line.split(": ").collect::<Vec<&str>>();
-      let ranges = key_value[1]
+      let (name, ranges) = line
+        .split_once(": ")
+        .ok_or_else(|| ParseError::new(DAY, input, line, "expected `<field>: <ranges>`"))?;
+      let ranges = ranges
         .split(" or ")
         .map(|r| {
-          let min_max = r.split("-").collect::<Vec<&str>>();
-          Range {
-            min: min_max[0].parse().unwrap(),
-            max: min_max[1].parse().unwrap(),
-          }
+          let (min, max) = r
+            .split_once('-')
+            .ok_or_else(|| ParseError::new(DAY, input, r, "expected `<min>-<max>`"))?;
+          Ok(Range {
+            min: parse_at(DAY, input, min, "expected a number")?,
+            max: parse_at(DAY, input, max, "expected a number")?,
+          })
         })
-        .collect();
-      (key_value[0].to_string(), ranges)
+        .collect::<Result<_, _>>()?;
+      Ok((name.to_string(), ranges))
     })
-    .collect();
-
-  let my_ticket: Ticket = blocks[1]
-    .split("\n")
-    .nth(1)
-    .unwrap()
-    .split(",")
-    .map(|x| x.parse().unwrap())
-    .collect();
-
-  let nearby_tickets: Vec<Ticket> = blocks[2]
-    .split("\n")
+    .collect::<Result<_, _>>()?;
+
+  let my_ticket: Ticket = parse_ticket(
+    input,
+    lines(blocks[1])
+      .nth(1)
+      .ok_or_else(|| ParseError::new(DAY, input, blocks[1], "expected your ticket"))?,
+  )?;
+
+  let nearby_tickets: Vec<Ticket> = lines(blocks[2])
     .skip(1)
-    .map(|row| row.split(",").map(|x| x.parse().unwrap()).collect())
-    .collect();
+    .map(|row| {
+      let ticket = parse_ticket(input, row)?;
+      if ticket.len() != my_ticket.len() {
+        return Err(ParseError::new(
+          DAY,
+          input,
+          row,
+          "all tickets must have the same number of values",
+        ));
+      }
+      Ok(ticket)
+    })
+    .collect::<Result<_, _>>()?;
 
-  Input {
+  Ok(Input {
     rules,
     my_ticket,
     nearby_tickets,
-  }
+  })
+}
+
+fn parse_ticket(input: &str, line: &str) -> Result<Ticket, ParseError> {
+  line
+    .split(',')
+    .map(|x| parse_at(DAY, input, x, "expected a ticket value"))
+    .collect()
 }
 
 #[aoc(day16, part1)]
@@ -101,7 +138,7 @@ pub fn solve_part2(input: &Input) -> u64 {
 
   let mut field_ordering: Vec<Option<String>> = vec![None; rules.len()];
 
-  while match_indices.iter().any(|indices| indices.len() != 0) {
+  while match_indices.iter().any(|indices| !indices.is_empty()) {
     let (rule_index, matches) = match_indices
       .iter()
       .enumerate()
@@ -117,7 +154,7 @@ pub fn solve_part2(input: &Input) -> u64 {
     field_ordering[match_index] = Some(rules[rule_index].0.clone());
   }
 
-  let answer = input
+  input
     .my_ticket
     .iter()
     .zip(field_ordering.iter())
@@ -128,9 +165,7 @@ pub fn solve_part2(input: &Input) -> u64 {
         .unwrap_or(false)
     })
     .map(|(field, _)| field)
-    .product();
-
-  return answer;
+    .product()
 }
 
 fn collect_rule_match_indices(tickets: &[&Ticket], rule: &Rule) -> HashSet<usize> {
@@ -143,10 +178,30 @@ fn collect_rule_match_indices(tickets: &[&Ticket], rule: &Rule) -> HashSet<usize
     .collect()
 }
 
+pub struct Day16;
+
+impl Solution for Day16 {
+  const DAY: u8 = DAY;
+
+  type Input = Input;
+
+  fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+    input_generator(input)
+  }
+
+  fn part1(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part1(input).into()
+  }
+
+  fn part2(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part2(input).into()
+  }
+}
+
 #[cfg(test)]
 mod test {
   use super::{input_generator, solve_part1};
-  const TEST_INPUT: &'static str = "class: 1-3 or 5-7\n\
+  const TEST_INPUT: &str = "class: 1-3 or 5-7\n\
   row: 6-11 or 33-44\n\
   seat: 13-40 or 45-50\n\
   \n\
@@ -161,7 +216,7 @@ mod test {
 
   #[test]
   fn test_day16_part1() {
-    let input = &input_generator(TEST_INPUT);
+    let input = input_generator(TEST_INPUT).unwrap();
     assert_eq!(solve_part1(&input), 71);
   }
 }
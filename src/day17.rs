@@ -1,124 +1,163 @@
-use std::collections::{HashMap, HashSet};
-
-pub fn input_generator(input: &str, n: usize) -> HashSet<Vec<i32>> {
-  let mut active = HashSet::<Vec<i32>>::new();
-  input.lines().enumerate().for_each(|(y, line)| {
-    line.chars().enumerate().for_each(|(x, c)| {
-      if c == '#' {
+use crate::{
+  answer::Answer,
+  automaton::{Automaton, Lattice, Rule},
+  diagnostics::{Diagnostics, Event, Silent},
+  grid::Grid,
+  solution::Solution,
+  ParseError,
+};
+use std::collections::HashSet;
+
+const DAY: u8 = 17;
+
+pub fn input_generator(input: &str, n: usize) -> Result<HashSet<Vec<i32>>, ParseError> {
+  let plane = Grid::parse(DAY, input, input, "expected `#` or `.`", |c| match c {
+    '#' => Some(true),
+    '.' => Some(false),
+    _ => None,
+  })?;
+
+  Ok(
+    plane
+      .positions()
+      .filter(|&position| plane[position])
+      .map(|(x, y)| {
         let mut v = vec![0; n];
         v[0] = x as i32;
         v[1] = y as i32;
-        active.insert(v);
-      }
-    })
-  });
-  return active;
+        v
+      })
+      .collect(),
+  )
 }
 
 #[aoc_generator(day17, part1)]
-pub fn input_generator_part1(input: &str) -> HashSet<Vec<i32>> {
+pub fn input_generator_part1(input: &str) -> Result<HashSet<Vec<i32>>, ParseError> {
   input_generator(input, 3)
 }
 
 #[aoc_generator(day17, part2)]
-pub fn input_generator_part2(input: &str) -> HashSet<Vec<i32>> {
+pub fn input_generator_part2(input: &str) -> Result<HashSet<Vec<i32>>, ParseError> {
   input_generator(input, 4)
 }
 
+/// Embeds the active cubes of a lower dimension into `n` dimensions.
+pub fn with_dimensions(active: &HashSet<Vec<i32>>, n: usize) -> HashSet<Vec<i32>> {
+  active
+    .iter()
+    .map(|pos| {
+      let mut pos = pos.clone();
+      pos.resize(n, 0);
+      pos
+    })
+    .collect()
+}
+
 #[aoc(day17, part1)]
 pub fn solve_part1(initial_active: &HashSet<Vec<i32>>) -> usize {
-  *conway_nd(3, initial_active, 6).last().unwrap()
+  *conway_nd(3, initial_active, 6, &Silent).last().unwrap()
 }
 
 #[aoc(day17, part2)]
 pub fn solve_part2(initial_active: &HashSet<Vec<i32>>) -> usize {
-  *conway_nd(4, initial_active, 6).last().unwrap()
+  *conway_nd(4, initial_active, 6, &Silent).last().unwrap()
 }
 
-pub fn conway_nd(n: usize, initial_active: &HashSet<Vec<i32>>, cycles: usize) -> Vec<usize> {
+pub fn conway_nd(
+  n: usize,
+  initial_active: &HashSet<Vec<i32>>,
+  cycles: usize,
+  diagnostics: &dyn Diagnostics,
+) -> Vec<usize> {
   let mut active_count = vec![initial_active.len()];
-  println!("intitial active: {}", initial_active.len());
-  let mut active = initial_active.clone();
-  let mut next_active = HashSet::<Vec<i32>>::new();
-
-  let neighbour_count: u128 = u128::pow(3, n as u32);
-  let neighbour_offsets = (0..neighbour_count)
-    .map(|i| {
-      let mut x = i;
-      let mut offset = Vec::with_capacity(n);
-      for _j in 0..n {
-        offset.push((x % 3) as i32 - 1);
-        x /= 3;
-      }
-      offset
-    })
-    .collect::<Vec<_>>();
-
-  let mut active_neighbour_count: HashMap<Vec<i32>, u32> = HashMap::new();
-  let mut inactive_neighbour_count: HashMap<Vec<i32>, u32> = HashMap::new();
+  diagnostics.report(Event::InitialActive {
+    count: initial_active.len(),
+  });
+  let mut automaton = Automaton::new(
+    Lattice::new(n),
+    Rule::new(&[3], &[2, 3]),
+    initial_active.iter().cloned(),
+  );
 
   for i in 0..cycles {
-    next_active.clear();
-
-    for pos in active.iter() {
-      for offset in neighbour_offsets.iter() {
-        let neighbour = pos
-          .iter()
-          .zip(offset.iter())
-          .map(|(p, o)| p + o)
-          .collect::<Vec<_>>();
-        if *pos == neighbour {
-          continue;
-        }
-        if active.contains(&neighbour) {
-          *active_neighbour_count.entry(neighbour).or_insert(0) += 1;
-        } else {
-          *inactive_neighbour_count.entry(neighbour).or_insert(0) += 1;
-        }
-      }
-    }
-
-    next_active.extend(active_neighbour_count.drain().filter_map(|(pos, count)| {
-      if count == 2 || count == 3 {
-        Some(pos)
-      } else {
-        None
-      }
-    }));
-
-    next_active.extend(inactive_neighbour_count.drain().filter_map(|(pos, count)| {
-      if count == 3 {
-        Some(pos)
-      } else {
-        None
-      }
-    }));
-
-    std::mem::swap(&mut active, &mut next_active);
-
-    active_count.push(active.len());
-    println!("cycle {}: {} active", i, active.len());
+    automaton.step();
+
+    active_count.push(automaton.alive_count());
+    diagnostics.report(Event::CycleFinished {
+      cycle: i,
+      active: automaton.alive_count(),
+    });
+  }
+
+  active_count
+}
+
+pub struct Day17;
+
+impl Solution for Day17 {
+  const DAY: u8 = DAY;
+
+  type Input = HashSet<Vec<i32>>;
+
+  fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+    input_generator(input, 2)
   }
 
-  return active_count;
+  fn part1(&self, input: &Self::Input, diagnostics: &dyn Diagnostics) -> Answer {
+    let active_count = conway_nd(3, &with_dimensions(input, 3), 6, diagnostics);
+    (*active_count.last().unwrap()).into()
+  }
+
+  fn part2(&self, input: &Self::Input, diagnostics: &dyn Diagnostics) -> Answer {
+    let active_count = conway_nd(4, &with_dimensions(input, 4), 6, diagnostics);
+    (*active_count.last().unwrap()).into()
+  }
 }
 
 #[cfg(test)]
 pub mod test {
 
-  use super::{input_generator_part1, input_generator_part2, solve_part1, solve_part2};
+  use super::{conway_nd, input_generator_part1, input_generator_part2, solve_part1, solve_part2};
+  use crate::diagnostics::{Collector, Event};
 
-  const TEST_INPUT: &'static str = ".#.\n\
+  const TEST_INPUT: &str = ".#.\n\
   ..#\n\
   ###";
 
   #[test]
   fn test_day17_part1() {
-    assert_eq!(solve_part1(&input_generator_part1(TEST_INPUT)), 112);
+    assert_eq!(
+      solve_part1(&input_generator_part1(TEST_INPUT).unwrap()),
+      112
+    );
   }
 
   #[test]
   fn test_day17_part2() {
-    assert_eq!(solve_part2(&input_generator_part2(TEST_INPUT)), 848);
+    assert_eq!(
+      solve_part2(&input_generator_part2(TEST_INPUT).unwrap()),
+      848
+    );
+  }
+
+  #[test]
+  fn test_day17_diagnostics() {
+    let collector = Collector::new();
+    conway_nd(
+      3,
+      &input_generator_part1(TEST_INPUT).unwrap(),
+      1,
+      &collector,
+    );
+    assert_eq!(
+      collector.into_events(),
+      vec![
+        Event::InitialActive { count: 5 },
+        Event::CycleFinished {
+          cycle: 0,
+          active: 11
+        }
+      ]
+    );
   }
 }
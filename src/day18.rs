@@ -1,5 +1,10 @@
+use crate::{
+  answer::Answer, diagnostics::Diagnostics, input::lines, solution::Solution, ParseError,
+};
 use std::iter::Peekable;
 
+const DAY: u8 = 18;
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum Operator {
   Plus,
@@ -15,21 +20,25 @@ pub enum Token {
 }
 
 #[aoc_generator(day18)]
-pub fn input_generator(input: &str) -> Vec<Vec<Token>> {
-  input
-    .lines()
+pub fn input_generator(input: &str) -> Result<Vec<Vec<Token>>, ParseError> {
+  lines(input)
     .map(|line| {
       line
-        .chars()
-        .filter_map(|c| match c {
-          '(' => Some(Token::LeftParen),
-          ')' => Some(Token::RightParen),
-          '+' => Some(Token::Operator(Operator::Plus)),
-          '*' => Some(Token::Operator(Operator::Times)),
+        .char_indices()
+        .filter_map(|(i, c)| match c {
+          '(' => Some(Ok(Token::LeftParen)),
+          ')' => Some(Ok(Token::RightParen)),
+          '+' => Some(Ok(Token::Operator(Operator::Plus))),
+          '*' => Some(Ok(Token::Operator(Operator::Times))),
           // There are only on-digit numbers, so this simplification is alright
-          _ if c.is_numeric() => Some(Token::Number(c.to_string().parse().unwrap())),
+          _ if c.is_ascii_digit() => Some(Ok(Token::Number(c.to_digit(10).unwrap() as u64))),
           ' ' => None,
-          _ => panic!("Invalid character '{}'!", c),
+          _ => Some(Err(ParseError::new(
+            DAY,
+            input,
+            &line[i..i + c.len_utf8()],
+            "invalid character",
+          ))),
         })
         .collect()
     })
@@ -56,15 +65,11 @@ fn evaluate_lines(lines: &[Vec<Token>], is_part2: bool) -> u64 {
 fn evaluate<'a>(tokens: &mut Peekable<impl Iterator<Item = &'a Token>>, is_part2: bool) -> u64 {
   let mut a = operand(tokens, is_part2);
 
-  loop {
-    if let Some(op) = operator(tokens) {
-      let b = operand(tokens, is_part2);
-      match op {
-        Operator::Plus => a += b,
-        Operator::Times => a *= b,
-      }
-    } else {
-      break;
+  while let Some(op) = operator(tokens) {
+    let b = operand(tokens, is_part2);
+    match op {
+      Operator::Plus => a += b,
+      Operator::Times => a *= b,
     }
   }
 
@@ -93,12 +98,32 @@ fn operator<'a>(tokens: &mut Peekable<impl Iterator<Item = &'a Token>>) -> Optio
   }
 }
 
+pub struct Day18;
+
+impl Solution for Day18 {
+  const DAY: u8 = DAY;
+
+  type Input = Vec<Vec<Token>>;
+
+  fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+    input_generator(input)
+  }
+
+  fn part1(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part1(input).into()
+  }
+
+  fn part2(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part2(input).into()
+  }
+}
+
 #[cfg(test)]
 mod test {
   use super::{input_generator, solve_part2};
 
   fn test_part2(input: &str, expected: u64) {
-    assert_eq!(solve_part2(&input_generator(input)), expected)
+    assert_eq!(solve_part2(&input_generator(input).unwrap()), expected)
   }
 
   #[test]
@@ -1,4 +1,14 @@
-use std::collections::HashMap;
+use crate::{
+  answer::Answer,
+  diagnostics::Diagnostics,
+  input::{blocks, lines},
+  parse_at,
+  solution::Solution,
+  ParseError,
+};
+use std::collections::{HashMap, HashSet};
+
+const DAY: u8 = 19;
 
 type TerminalRule = (usize, char);
 type ProductionRule = (usize, usize, usize);
@@ -12,8 +22,8 @@ pub struct Input {
 }
 
 #[aoc_generator(day19)]
-pub fn input_generator(input: &str) -> Input {
-  let mut blocks = input.split("\n\n");
+pub fn input_generator(input: &str) -> Result<Input, ParseError> {
+  let mut blocks = blocks(input);
 
   let mut terminal_rules: Vec<TerminalRule> = Vec::new();
   let mut production_rules: Vec<ProductionRule> = Vec::new();
@@ -21,35 +31,62 @@ pub fn input_generator(input: &str) -> Input {
   let mut non_terminal_count = 0;
 
   let mut unit_rules: HashMap<usize, Vec<usize>> = HashMap::new();
+  let mut defined = HashSet::new();
+  let mut references = Vec::new();
 
-  blocks.next().unwrap().lines().for_each(|rule| {
-    let mut left_right = rule.split(": ");
-    let left = left_right.next().unwrap().parse::<usize>().unwrap();
+  let rules = blocks
+    .next()
+    .ok_or_else(|| ParseError::new(DAY, input, input, "expected the rules"))?;
+  for rule in lines(rules) {
+    let (left, right) = rule
+      .split_once(": ")
+      .ok_or_else(|| ParseError::new(DAY, input, rule, "expected `<rule>: <alternatives>`"))?;
+    let left = parse_at::<usize>(DAY, input, left, "expected a rule number")?;
     non_terminal_count = usize::max(non_terminal_count, left);
-    let right = left_right.next().unwrap();
-    if right.starts_with("\"") {
-      terminal_rules.push((left, right.chars().nth(1).unwrap()));
+    defined.insert(left);
+    if right.starts_with('"') {
+      let terminal = right
+        .chars()
+        .nth(1)
+        .filter(|_| right.len() > 2 && right.ends_with('"'))
+        .ok_or_else(|| ParseError::new(DAY, input, right, "expected a quoted character"))?;
+      terminal_rules.push((left, terminal));
     } else {
-      right.split(" | ").for_each(|rule| {
+      for rule in right.split(" | ") {
         let non_terminals = rule
-          .split(" ")
-          .map(|x| x.parse::<usize>().unwrap())
-          .collect::<Vec<_>>();
+          .split(' ')
+          .map(|x| {
+            let id = parse_at::<usize>(DAY, input, x, "expected a rule number")?;
+            references.push((id, x));
+            Ok(id)
+          })
+          .collect::<Result<Vec<_>, _>>()?;
         match non_terminals.len() {
           1 => {
-            unit_rules
-              .entry(left)
-              .or_insert(Vec::new())
-              .push(non_terminals[0]);
+            unit_rules.entry(left).or_default().push(non_terminals[0]);
           }
           2 => {
             production_rules.push((left, non_terminals[0], non_terminals[1]));
           }
-          _ => unreachable!(),
+          _ => {
+            return Err(ParseError::new(
+              DAY,
+              input,
+              rule,
+              "alternatives may only consist of one or two rules",
+            ))
+          }
         }
-      });
+      }
     }
-  });
+  }
+
+  if !defined.contains(&0) {
+    return Err(ParseError::new(DAY, input, rules, "there is no rule 0"));
+  }
+  if let Some((_, reference)) = references.iter().find(|(id, _)| !defined.contains(id)) {
+    return Err(ParseError::new(DAY, input, reference, "undefined rule"));
+  }
 
   non_terminal_count += 1;
 
@@ -91,19 +128,22 @@ pub fn input_generator(input: &str) -> Input {
 
   production_rules.extend(additional_rules);
 
-  let words = blocks
-    .next()
-    .unwrap()
-    .lines()
-    .map(|x| x.to_string())
-    .collect();
-
-  Input {
+  let messages = blocks.next().ok_or_else(|| {
+    ParseError::new(
+      DAY,
+      input,
+      &input[input.len()..],
+      "expected messages after the rules",
+    )
+  })?;
+  let words = lines(messages).map(|x| x.to_string()).collect();
+
+  Ok(Input {
     non_terminal_count,
     terminal_rules,
     production_rules,
     words,
-  }
+  })
 }
 
 #[aoc(day19, part1)]
@@ -116,8 +156,12 @@ pub fn solve_part1(input: &Input) -> usize {
   )
 }
 
+/// Replaces rules 8 and 11 with loops, which needs the rules 11, 31 and 42 of the puzzle.
 #[aoc(day19, part2)]
-pub fn solve_part2(input: &Input) -> usize {
+pub fn solve_part2(input: &Input) -> Option<usize> {
+  if input.non_terminal_count <= 42 {
+    return None;
+  }
   let mut extended_production_rules = input.production_rules.clone();
 
   extended_production_rules.push((42, 42, 42));
@@ -129,12 +173,12 @@ pub fn solve_part2(input: &Input) -> usize {
 
   non_terminal_count += 1;
 
-  count_words_in_language(
+  Some(count_words_in_language(
     &input.words,
     non_terminal_count,
     &extended_production_rules,
     &input.terminal_rules,
-  )
+  ))
 }
 
 fn count_words_in_language(
@@ -157,19 +201,21 @@ fn cyk(
   terminal_rules: &[TerminalRule],
 ) -> bool {
   let chars: Vec<char> = word.chars().collect();
-  let n = word.len();
+  let n = chars.len();
   let r = non_terminal_count;
+  if n == 0 {
+    return false;
+  }
 
   let mut matrix = vec![vec![vec![false; r]; n]; n];
 
   for s in 0..n {
     let terminal = chars[s];
-    let left = terminal_rules
-      .iter()
-      .find(|(_, t)| *t == terminal)
-      .unwrap()
-      .0;
-    matrix[0][s][left] = true;
+    match terminal_rules.iter().find(|(_, t)| *t == terminal) {
+      Some(&(left, _)) => matrix[0][s][left] = true,
+      // no rule produces this character
+      None => return false,
+    }
   }
 
   for l in 1..n {
@@ -193,15 +239,35 @@ fn cyk(
     println!("word '{}' isn't a member of the language!", word);
   }*/
 
-  return is_member_of_language;
+  is_member_of_language
+}
+
+pub struct Day19;
+
+impl Solution for Day19 {
+  const DAY: u8 = DAY;
+
+  type Input = Input;
+
+  fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+    input_generator(input)
+  }
+
+  fn part1(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part1(input).into()
+  }
+
+  fn part2(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part2(input).into()
+  }
 }
 
 #[cfg(test)]
 mod test {
 
-  use super::{input_generator, solve_part1};
+  use super::{input_generator, solve_part1, solve_part2};
 
-  const TEST_INPUT: &'static str = "0: 5 1\n\
+  const TEST_INPUT: &str = "0: 5 1\n\
   1: 2 6\n\
   2: 3 4 | 4 3\n\
   3: 5 5 | 6 6\n\
@@ -217,6 +283,18 @@ mod test {
 
   #[test]
   fn test_day19_part1() {
-    assert_eq!(solve_part1(&input_generator(TEST_INPUT)), 2);
+    assert_eq!(solve_part1(&input_generator(TEST_INPUT).unwrap()), 2);
+  }
+
+  #[test]
+  fn test_day19_invalid_rules() {
+    let error = input_generator("0: 1 2\n1: \"a\"\n\nab").unwrap_err();
+    assert_eq!((error.line, error.column), (1, 6));
+    assert_eq!(error.reason, "undefined rule");
+    assert!(input_generator("1: \"a\"\n\na").is_err());
+
+    let input = input_generator("0: 1 1\n1: \"a\"\n\naa\nab\nb").unwrap();
+    assert_eq!(solve_part1(&input), 1);
+    assert_eq!(solve_part2(&input), None);
   }
 }
@@ -1,11 +1,22 @@
+use crate::{
+  answer::Answer,
+  diagnostics::{Diagnostics, Event, Silent},
+  grid::Grid,
+  input::{blocks, lines},
+  parse_at,
+  solution::Solution,
+  ParseError,
+};
 use regex::Regex;
 use std::fmt;
 use std::str::FromStr;
 
+const DAY: u8 = 20;
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Tile {
   id: u64,
-  data: Vec<Vec<char>>,
+  data: Grid<char>,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -17,6 +28,15 @@ pub enum Border {
 }
 
 impl Border {
+  pub fn name(self) -> &'static str {
+    match self {
+      Border::Top => "top",
+      Border::Right => "right",
+      Border::Bottom => "bottom",
+      Border::Left => "left",
+    }
+  }
+
   pub fn opposite(self) -> Border {
     match self {
       Border::Top => Border::Bottom,
@@ -27,96 +47,118 @@ impl Border {
   }
 }
 
+lazy_static! {
+  static ref TILE_HEADER_REGEX: Regex = Regex::new(r"^Tile (\d+):$").unwrap();
+}
+
 const BORDERS: [Border; 4] = [Border::Top, Border::Right, Border::Bottom, Border::Left];
 
 impl Tile {
   pub fn width(&self) -> usize {
-    self.data[0].len()
+    self.data.width()
   }
 
   pub fn height(&self) -> usize {
-    self.data.len()
+    self.data.height()
   }
 
   pub fn flip(&mut self) {
-    self.data.iter_mut().for_each(|row| row.reverse())
+    self.data.flip_horizontal()
   }
 
   pub fn rotate_cw(&mut self) {
-    self.data = (0..self.width())
-      .map(|col| {
-        (0..self.height())
-          .rev()
-          .map(|row| self.data[row][col])
-          .collect()
-      })
-      .collect();
+    self.data.rotate_cw()
   }
 
   pub fn get_border(&self, border: Border) -> String {
     match border {
-      Border::Top => self.data[0].iter().collect(),
-      Border::Bottom => self.data[self.height() - 1].iter().collect(),
-      Border::Left => self.data.iter().map(|row| row[0]).collect(),
-      Border::Right => self.data.iter().map(|row| row[self.width() - 1]).collect(),
+      Border::Top => self.data.row(0).iter().collect(),
+      Border::Bottom => self.data.row(self.height() - 1).iter().collect(),
+      Border::Left => self.data.column(0).collect(),
+      Border::Right => self.data.column(self.width() - 1).collect(),
     }
   }
 
   pub fn matches_tile(&self, tile: &Tile) -> Option<Border> {
-    for &border in &BORDERS {
-      if self.get_border(border) == tile.get_border(border.opposite()) {
-        return Some(border);
-      }
+    BORDERS
+      .iter()
+      .copied()
+      .find(|&border| self.get_border(border) == tile.get_border(border.opposite()))
+  }
+
+  /// Parses the tile `block`, which is a slice of `input`.
+  fn parse(input: &str, block: &str) -> Result<Tile, ParseError> {
+    let header = lines(block).next().unwrap();
+    let id = TILE_HEADER_REGEX
+      .captures(header)
+      .ok_or_else(|| ParseError::new(DAY, input, header, "expected `Tile <id>:`"))?
+      .get(1)
+      .unwrap()
+      .as_str();
+    let id = parse_at(DAY, input, id, "invalid tile id")?;
+
+    let data = Grid::parse(
+      DAY,
+      input,
+      block[header.len()..].trim_start(),
+      "expected `#` or `.`",
+      |c| match c {
+        '#' | '.' => Some(c),
+        _ => None,
+      },
+    )?;
+
+    if data.width() != data.height() {
+      return Err(ParseError::new(
+        DAY,
+        input,
+        block,
+        "a tile has to be a square",
+      ));
     }
-    return None;
+
+    Ok(Tile { id, data })
   }
 }
 
 impl FromStr for Tile {
-  type Err = ();
+  type Err = ParseError;
 
   fn from_str(s: &str) -> Result<Self, Self::Err> {
-    let re = Regex::new(r"^Tile (\d+):$").unwrap();
-    let mut lines = s.split("\n");
-    let id = re
-      .captures(lines.next().unwrap())
-      .unwrap()
-      .get(1)
-      .unwrap()
-      .as_str()
-      .parse::<u64>()
-      .unwrap();
-
-    let data = lines.map(|line| line.chars().collect()).collect();
-
-    Ok(Tile { id, data })
+    Tile::parse(s, s)
   }
 }
 
 impl fmt::Display for Tile {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-    f.write_str(
-      &self
-        .data
-        .iter()
-        .map(|row| row.iter().collect::<String>())
-        .collect::<Vec<_>>()
-        .join("\n"),
-    )
+    self.data.fmt(f)
   }
 }
 
 #[aoc_generator(day20)]
-pub fn input_generator(input: &str) -> Vec<Tile> {
-  input
-    .split("\n\n")
-    .map(|tile| Tile::from_str(tile).unwrap())
-    .collect()
+pub fn input_generator(input: &str) -> Result<Vec<Tile>, ParseError> {
+  let tiles = blocks(input)
+    .map(|tile| Tile::parse(input, tile))
+    .collect::<Result<Vec<_>, _>>()?;
+  let side_length = f64::sqrt(tiles.len() as f64) as usize;
+  if tiles.is_empty() || side_length * side_length != tiles.len() {
+    return Err(ParseError::new(
+      DAY,
+      input,
+      input,
+      "the number of tiles has to be a square number",
+    ));
+  }
+  Ok(tiles)
 }
 
 #[aoc(day20, part1)]
 pub fn solve_part1(tiles: &[Tile]) -> u64 {
-  let puzzle = solve_puzzle(tiles);
+  solve_part1_with(tiles, &Silent)
+}
+
+pub fn solve_part1_with(tiles: &[Tile], diagnostics: &dyn Diagnostics) -> u64 {
+  let puzzle = solve_puzzle(tiles, diagnostics);
   let l = puzzle.len() - 1;
 
   [(0, 0), (0, l), (l, 0), (l, l)]
@@ -127,55 +169,45 @@ pub fn solve_part1(tiles: &[Tile]) -> u64 {
 
 #[aoc(day20, part2)]
 pub fn solve_part2(tiles: &[Tile]) -> usize {
-  let puzzle = solve_puzzle(tiles);
-  let puzzle_size = puzzle.len();
-  let tile_size = puzzle[0][0].as_ref().unwrap().data.len() - 2;
-  let row_count = puzzle_size * tile_size;
-  let mut sea_picture = Vec::<String>::with_capacity(row_count + 1);
-  sea_picture.push(String::from("Tile 1000:"));
-
-  for row in 0..row_count {
-    let mut row_str = String::new();
-    for col in 0..puzzle_size {
-      let tile = puzzle[row / tile_size][col].as_ref().unwrap();
-      row_str.push_str(
-        &tile.data[1 + row % tile_size]
-          .iter()
-          .skip(1)
-          .take(tile_size)
-          .collect::<String>(),
-      );
-    }
-    sea_picture.push(row_str);
-  }
+  solve_part2_with(tiles, &Silent)
+}
 
-  let sea_picture = sea_picture.join("\n");
-  let total_hashtag_count = sea_picture.chars().filter(|c| *c == '#').count();
-  let mut sea_picture = Tile::from_str(&sea_picture).unwrap();
+pub fn solve_part2_with(tiles: &[Tile], diagnostics: &dyn Diagnostics) -> usize {
+  let puzzle = solve_puzzle(tiles, diagnostics);
+  let puzzle_size = puzzle.len();
+  let tile_size = puzzle[0][0].as_ref().unwrap().width() - 2;
+  let picture_size = puzzle_size * tile_size;
 
-  const SEA_MONSTER: &str = "                  # \n#    ##    ##    ###\n #  #  #  #  #  #   ";
+  // the picture is made of the tiles without their borders
+  let mut sea_picture = Grid::from_fn(picture_size, picture_size, |x, y| {
+    let tile = puzzle[y / tile_size][x / tile_size].as_ref().unwrap();
+    tile.data[(1 + x % tile_size, 1 + y % tile_size)]
+  });
+  let total_hashtag_count = sea_picture.iter().filter(|&&c| c == '#').count();
 
-  let monster_hashtag_count = SEA_MONSTER.chars().filter(|c| *c == '#').count();
-  let monster_width = SEA_MONSTER.split("\n").next().unwrap().len();
-  let monster_height = SEA_MONSTER.split("\n").count();
+  const SEA_MONSTER: [&str; 3] = [
+    "                  # ",
+    "#    ##    ##    ###",
+    " #  #  #  #  #  #   ",
+  ];
 
-  let mut monster_indices: Vec<(usize, usize)> = Vec::new();
-  SEA_MONSTER.split("\n").enumerate().for_each(|(y, line)| {
-    line.chars().enumerate().for_each(|(x, c)| {
-      if c == '#' {
-        monster_indices.push((x, y));
-      }
-    })
+  let monster = Grid::from_fn(SEA_MONSTER[0].len(), SEA_MONSTER.len(), |x, y| {
+    SEA_MONSTER[y].as_bytes()[x] == b'#'
   });
+  let monster_indices = monster
+    .positions()
+    .filter(|&position| monster[position])
+    .collect::<Vec<_>>();
+  let monster_hashtag_count = monster_indices.len();
 
   let mut monster_count = 0;
 
   for i in 0..8 {
-    for row in 0..sea_picture.height() - monster_height {
-      for col in 0..sea_picture.width() - monster_width {
+    for row in 0..sea_picture.height() - monster.height() {
+      for col in 0..sea_picture.width() - monster.width() {
         if monster_indices
           .iter()
-          .all(|(x, y)| sea_picture.data[row + y][col + x] == '#')
+          .all(|(x, y)| sea_picture[(col + x, row + y)] == '#')
         {
           monster_count += 1;
         }
@@ -189,26 +221,28 @@ pub fn solve_part2(tiles: &[Tile]) -> usize {
     sea_picture.rotate_cw();
 
     if i == 3 {
-      sea_picture.flip();
+      sea_picture.flip_horizontal();
     }
   }
 
-  println!("{} monsters found!", monster_count);
+  diagnostics.report(Event::MonstersFound {
+    count: monster_count,
+  });
 
-  let roughness = total_hashtag_count - monster_hashtag_count * monster_count;
-  return roughness;
+  total_hashtag_count - monster_hashtag_count * monster_count
 }
 
-fn solve_puzzle(tiles: &[Tile]) -> Vec<Vec<Option<Tile>>> {
+#[allow(clippy::needless_range_loop)]
+fn solve_puzzle(tiles: &[Tile], diagnostics: &dyn Diagnostics) -> Vec<Vec<Option<Tile>>> {
   let side_length = f64::sqrt(tiles.len() as f64) as usize;
-  println!("puzzle side length: {}", side_length);
+  diagnostics.report(Event::PuzzleSideLength { side_length });
 
-  let mut remaining_tiles = tiles.iter().cloned().collect::<Vec<_>>();
+  let mut remaining_tiles = tiles.to_vec();
   let mut puzzle: Vec<Vec<Option<Tile>>> = vec![vec![None; side_length]; side_length];
 
   puzzle[side_length / 2][side_length / 2] = Some(remaining_tiles.pop().unwrap());
 
-  while remaining_tiles.len() > 0 {
+  while !remaining_tiles.is_empty() {
     let mut next_tile = Some(remaining_tiles.pop().unwrap());
 
     for row in 0..side_length {
@@ -229,13 +263,12 @@ fn solve_puzzle(tiles: &[Tile]) -> Vec<Vec<Option<Tile>>> {
         }
 
         if let Some(border) = match_border {
-          println!(
-            "match piece {:?} at ({:?} {:?} {:?})",
-            next_tile.as_ref().unwrap().id,
+          diagnostics.report(Event::TileMatched {
+            id: next_tile.as_ref().unwrap().id,
             row,
             col,
-            border
-          );
+            border: border.name(),
+          });
           match border {
             Border::Top => {
               if row == 0 {
@@ -294,22 +327,42 @@ fn solve_puzzle(tiles: &[Tile]) -> Vec<Vec<Option<Tile>>> {
       }
     }
 
-    if next_tile.is_some() {
+    if let Some(next_tile) = next_tile {
       // Piece couldn't be matched! so insert it back!
-      remaining_tiles.insert(0, next_tile.unwrap());
+      remaining_tiles.insert(0, next_tile);
     }
   }
 
   puzzle
 }
 
+pub struct Day20;
+
+impl Solution for Day20 {
+  const DAY: u8 = DAY;
+
+  type Input = Vec<Tile>;
+
+  fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+    input_generator(input)
+  }
+
+  fn part1(&self, input: &Self::Input, diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part1_with(input, diagnostics).into()
+  }
+
+  fn part2(&self, input: &Self::Input, diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part2_with(input, diagnostics).into()
+  }
+}
+
 #[cfg(test)]
 mod test {
 
   use super::{input_generator, solve_part1, solve_part2, Tile};
   use std::str::FromStr;
 
-  const TEST_INPUT: &'static str = "Tile 1234:\n\
+  const TEST_INPUT: &str = "Tile 1234:\n\
   ..#.\n\
   ##.#\n\
   ..##\n\
@@ -331,7 +384,7 @@ mod test {
     assert_eq!(tile.to_string(), expected);
   }
 
-  const TEST_PUZZLE: &'static str = "Tile 2311:\n\
+  const TEST_PUZZLE: &str = "Tile 2311:\n\
   ..##.#..#.\n\
   ##..#.....\n\
   #...##..#.\n\
@@ -441,13 +494,13 @@ mod test {
 
   #[test]
   fn test_day20_part1() {
-    let tiles = input_generator(TEST_PUZZLE);
+    let tiles = input_generator(TEST_PUZZLE).unwrap();
     assert_eq!(solve_part1(&tiles), 20899048083289);
   }
 
   #[test]
   fn test_day20_part2() {
-    let tiles = input_generator(TEST_PUZZLE);
+    let tiles = input_generator(TEST_PUZZLE).unwrap();
     assert_eq!(solve_part2(&tiles), 273);
   }
 }
@@ -1,36 +1,40 @@
+use crate::{
+  answer::Answer, diagnostics::Diagnostics, input::lines, solution::Solution, ParseError,
+};
 use regex::Regex;
 use std::collections::{HashMap, HashSet};
 
+const DAY: u8 = 21;
+
 type Food = (HashSet<String>, HashSet<String>);
 
 #[aoc_generator(day21)]
-pub fn input_generator(input: &str) -> Vec<Food> {
-  let regex = Regex::new(r"contains (\w+(, \w+)*)").unwrap();
-  input
-    .lines()
+pub fn input_generator(input: &str) -> Result<Vec<Food>, ParseError> {
+  let regex = Regex::new(r"^contains (\w+(, \w+)*)\)$").unwrap();
+  lines(input)
     .map(|line| {
       let mut ingredients_allergens = line.split(" (");
       let ingredients = ingredients_allergens
         .next()
         .unwrap()
-        .split(" ")
+        .split(' ')
         .map(|ingr| ingr.to_string())
         .collect();
-      let allergens = ingredients_allergens
-        .next()
-        .map(|allergens| {
-          regex
-            .captures(allergens)
-            .unwrap()
-            .get(1)
-            .unwrap()
-            .as_str()
-            .split(", ")
-            .map(|alg| alg.to_string())
-            .collect()
-        })
-        .unwrap_or_default();
-      (ingredients, allergens)
+      let allergens = match ingredients_allergens.next() {
+        Some(allergens) => regex
+          .captures(allergens)
+          .ok_or_else(|| {
+            ParseError::new(DAY, input, allergens, "expected `contains <allergens>)`")
+          })?
+          .get(1)
+          .unwrap()
+          .as_str()
+          .split(", ")
+          .map(|alg| alg.to_string())
+          .collect(),
+        None => HashSet::new(),
+      };
+      Ok((ingredients, allergens))
     })
     .collect()
 }
@@ -73,8 +77,8 @@ fn get_ingredients_with_allergenes(food: &[Food]) -> HashMap<String, String> {
     for a in allergens.iter() {
       let ingr_set = allergens_to_ingr
         .entry(a.clone())
-        .or_insert(ingredients.clone());
-      *ingr_set = ingr_set.intersection(&ingredients).cloned().collect();
+        .or_insert_with(|| ingredients.clone());
+      *ingr_set = ingr_set.intersection(ingredients).cloned().collect();
     }
   }
 
@@ -103,37 +107,57 @@ fn get_ingredients_with_allergenes(food: &[Food]) -> HashMap<String, String> {
       }
     }
 
-    if next_determined_ingredients.len() == 0 {
+    if next_determined_ingredients.is_empty() {
       break;
     }
 
     determined_ingredients = next_determined_ingredients;
   }
 
-  return allergens_to_ingr
+  allergens_to_ingr
     .into_iter()
     .map(|(allergene, ingr_s)| (ingr_s.into_iter().next().unwrap(), allergene))
-    .collect();
+    .collect()
+}
+
+pub struct Day21;
+
+impl Solution for Day21 {
+  const DAY: u8 = DAY;
+
+  type Input = Vec<Food>;
+
+  fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+    input_generator(input)
+  }
+
+  fn part1(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part1(input).into()
+  }
+
+  fn part2(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part2(input).into()
+  }
 }
 
 #[cfg(test)]
 mod test {
   use super::{input_generator, solve_part1, solve_part2};
 
-  const TEST_INPUT: &'static str = "mxmxvkd kfcds sqjhc nhms (contains dairy, fish)\n\
+  const TEST_INPUT: &str = "mxmxvkd kfcds sqjhc nhms (contains dairy, fish)\n\
 	trh fvjkl sbzzf mxmxvkd (contains dairy)\n\
 	sqjhc fvjkl (contains soy)\n\
 	sqjhc mxmxvkd sbzzf (contains fish)";
 
   #[test]
   fn test_day21_part1() {
-    let food = input_generator(TEST_INPUT);
+    let food = input_generator(TEST_INPUT).unwrap();
     assert_eq!(solve_part1(&food), 5);
   }
 
   #[test]
   fn test_day21_part2() {
-    let food = input_generator(TEST_INPUT);
+    let food = input_generator(TEST_INPUT).unwrap();
     assert_eq!(solve_part2(&food), String::from("mxmxvkd,sqjhc,fvjkl"));
   }
 }
@@ -1,26 +1,37 @@
+use crate::{
+  answer::Answer,
+  diagnostics::Diagnostics,
+  input::{blocks, lines},
+  parse_at,
+  solution::Solution,
+  ParseError,
+};
 use std::collections::{HashSet, VecDeque};
 
+const DAY: u8 = 22;
+
 #[aoc_generator(day22)]
-pub fn input_generator(input: &str) -> (VecDeque<u16>, VecDeque<u16>) {
-  let mut players = input.split("\n\n");
-
-  let p1 = players
-    .next()
-    .unwrap()
-    .split("\n")
-    .skip(1)
-    .map(|line| line.parse().unwrap())
-    .collect();
-
-  let p2 = players
-    .next()
-    .unwrap()
-    .split("\n")
-    .skip(1)
-    .map(|line| line.parse().unwrap())
-    .collect();
-
-  return (p1, p2);
+pub fn input_generator(input: &str) -> Result<(VecDeque<u16>, VecDeque<u16>), ParseError> {
+  let mut players = blocks(input);
+  let mut parse_deck = || {
+    let deck = players.next().ok_or_else(|| {
+      ParseError::new(
+        DAY,
+        input,
+        &input[input.len()..],
+        "expected the decks of two players",
+      )
+    })?;
+    lines(deck)
+      .skip(1)
+      .map(|line| parse_at(DAY, input, line, "expected a card"))
+      .collect::<Result<VecDeque<u16>, _>>()
+  };
+
+  let p1 = parse_deck()?;
+  let p2 = parse_deck()?;
+
+  Ok((p1, p2))
 }
 
 #[aoc(day22, part1)]
@@ -28,7 +39,7 @@ pub fn solve_part1((p1, p2): &(VecDeque<u16>, VecDeque<u16>)) -> u16 {
   let mut p1 = p1.clone();
   let mut p2 = p2.clone();
 
-  while p1.len() > 0 && p2.len() > 0 {
+  while !p1.is_empty() && !p2.is_empty() {
     let p1_card = p1.pop_front().unwrap();
     let p2_card = p2.pop_front().unwrap();
 
@@ -41,9 +52,9 @@ pub fn solve_part1((p1, p2): &(VecDeque<u16>, VecDeque<u16>)) -> u16 {
     }
   }
 
-  let winner = if p1.len() > 0 { p1 } else { p2 };
+  let winner = if !p1.is_empty() { p1 } else { p2 };
 
-  return score(&winner);
+  score(&winner)
 }
 
 fn score(cards: &VecDeque<u16>) -> u16 {
@@ -73,7 +84,7 @@ pub fn recursive_combat(
   let mut prev_p1_rounds: HashSet<VecDeque<u16>> = HashSet::new();
   let mut prev_p2_rounds: HashSet<VecDeque<u16>> = HashSet::new();
 
-  while p1.len() > 0 && p2.len() > 0 {
+  while !p1.is_empty() && !p2.is_empty() {
     if prev_p1_rounds.contains(&p1) || prev_p2_rounds.contains(&p2) {
       return (Winner::Player1, p1);
     }
@@ -105,19 +116,39 @@ pub fn recursive_combat(
     }
   }
 
-  if p1.len() > 0 {
+  if !p1.is_empty() {
     (Winner::Player1, p1)
   } else {
     (Winner::Player2, p2)
   }
 }
 
+pub struct Day22;
+
+impl Solution for Day22 {
+  const DAY: u8 = DAY;
+
+  type Input = (VecDeque<u16>, VecDeque<u16>);
+
+  fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+    input_generator(input)
+  }
+
+  fn part1(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part1(input).into()
+  }
+
+  fn part2(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part2(input).into()
+  }
+}
+
 #[cfg(test)]
 mod test {
 
   use super::{input_generator, solve_part1, solve_part2};
 
-  const TEST_INPUT: &'static str = "Player 1:\n\
+  const TEST_INPUT: &str = "Player 1:\n\
 	9\n\
 	2\n\
 	6\n\
@@ -133,11 +164,11 @@ mod test {
 
   #[test]
   fn test_day22_part1() {
-    assert_eq!(solve_part1(&input_generator(TEST_INPUT)), 306);
+    assert_eq!(solve_part1(&input_generator(TEST_INPUT).unwrap()), 306);
   }
 
   #[test]
   fn test_day22_part2() {
-    assert_eq!(solve_part2(&input_generator(TEST_INPUT)), 291);
+    assert_eq!(solve_part2(&input_generator(TEST_INPUT).unwrap()), 291);
   }
 }
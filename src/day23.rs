@@ -1,15 +1,44 @@
+use crate::{answer::Answer, diagnostics::Diagnostics, solution::Solution, ParseError};
 use std::iter;
 
+const DAY: u8 = 23;
+
 #[aoc_generator(day23)]
-pub fn input_generator(input: &str) -> Vec<usize> {
-	input
-		.chars()
-		.map(|x| x.to_string().parse().unwrap())
-		.collect()
+pub fn input_generator(input: &str) -> Result<Vec<usize>, ParseError> {
+	let cups = input
+		.trim_end()
+		.char_indices()
+		.map(|(i, c)| {
+			c.to_digit(10)
+				.filter(|&d| d > 0)
+				.map(|d| d as usize)
+				.ok_or_else(|| {
+					ParseError::new(
+						DAY,
+						input,
+						&input[i..i + c.len_utf8()],
+						"expected a cup label",
+					)
+				})
+		})
+		.collect::<Result<Vec<_>, _>>()?;
+
+	let mut sorted = cups.clone();
+	sorted.sort_unstable();
+	if cups.is_empty() || sorted.iter().copied().ne(1..=cups.len()) {
+		return Err(ParseError::new(
+			DAY,
+			input,
+			input,
+			"the cups have to be labeled from 1 to their count",
+		));
+	}
+
+	Ok(cups)
 }
 
 #[aoc(day23, part1)]
-fn solve_part1(cups: &[usize]) -> String {
+pub fn solve_part1(cups: &[usize]) -> String {
 	let succ = cup_game_fast(cups, 100);
 
 	let mut cup_str = String::new();
@@ -20,11 +49,11 @@ fn solve_part1(cups: &[usize]) -> String {
 		cup_str.push_str(&i.to_string());
 	}
 
-	return cup_str;
+	cup_str
 }
 
 #[aoc(day23, part2)]
-fn solve_part2(_cups: &[usize]) -> usize {
+pub fn solve_part2(_cups: &[usize]) -> usize {
 	let mut cups: Vec<usize> = Vec::with_capacity(1_000_000);
 	cups.extend(_cups.iter());
 	let max = cups.iter().max().unwrap();
@@ -37,13 +66,13 @@ fn solve_part2(_cups: &[usize]) -> usize {
 	let c1 = succ[1];
 	let c2 = succ[c1];
 
-	return c1 * c2;
+	c1 * c2
 }
 
 // slow version of the cup game, with cups just in vec and vec manipulations
 #[allow(dead_code)]
 fn cup_game_slow(cups: &[usize], num_moves: usize) -> String {
-	let mut cups: Vec<usize> = cups.iter().cloned().collect();
+	let mut cups: Vec<usize> = cups.to_vec();
 	let l = cups.len();
 
 	for _ in 0..num_moves {
@@ -62,7 +91,7 @@ fn cup_game_slow(cups: &[usize], num_moves: usize) -> String {
 		}
 
 		let dest_index = cups.iter().position(|x| *x == dest).unwrap() + 1;
-		cups.splice(dest_index..dest_index, rem_cups.into_iter());
+		cups.splice(dest_index..dest_index, rem_cups);
 		cups.push(curr);
 	}
 
@@ -80,13 +109,13 @@ fn cup_game_slow(cups: &[usize], num_moves: usize) -> String {
 
 // fast version of the cup game, storing the successor of a cup in a vec
 fn cup_game_fast(cups: &[usize], num_moves: usize) -> Vec<usize> {
-	let mut curr = cups[0].clone();
+	let mut curr = cups[0];
 
 	// add plus one to the cup size sow that we have a quasi one index array
 	let mut succ: Vec<usize> = vec![0; cups.len() + 1];
 
-	for i in 0..cups.len() {
-		succ[cups[i]] = cups[(i + 1) % cups.len()];
+	for (i, cup) in cups.iter().enumerate() {
+		succ[*cup] = cups[(i + 1) % cups.len()];
 	}
 
 	for _ in 0..num_moves {
@@ -105,5 +134,25 @@ fn cup_game_fast(cups: &[usize], num_moves: usize) -> Vec<usize> {
 		curr = succ[curr];
 	}
 
-	return succ;
+	succ
+}
+
+pub struct Day23;
+
+impl Solution for Day23 {
+	const DAY: u8 = DAY;
+
+	type Input = Vec<usize>;
+
+	fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+		input_generator(input)
+	}
+
+	fn part1(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+		solve_part1(input).into()
+	}
+
+	fn part2(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+		solve_part2(input).into()
+	}
 }
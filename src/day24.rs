@@ -1,5 +1,15 @@
+use crate::{
+  answer::Answer,
+  automaton::{Automaton, Hex, Rule},
+  diagnostics::Diagnostics,
+  input::lines,
+  solution::Solution,
+  ParseError,
+};
 use regex::Regex;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
+
+const DAY: u8 = 24;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Direction {
@@ -14,22 +24,43 @@ pub enum Direction {
 type Tile = Vec<Direction>;
 
 #[aoc_generator(day24)]
-pub fn input_generator(input: &str) -> Vec<Tile> {
+pub fn input_generator(input: &str) -> Result<Vec<Tile>, ParseError> {
   let re = Regex::new(r"e|se|sw|w|nw|ne").unwrap();
-  input
-    .split("\n")
+  lines(input)
     .map(|line| {
-      re.find_iter(line)
-        .map(|dir| match dir.as_str() {
-          "e" => Direction::East,
-          "se" => Direction::SouthEast,
-          "sw" => Direction::SouthWest,
-          "w" => Direction::West,
-          "nw" => Direction::NorthWest,
-          "ne" => Direction::NorthEast,
-          _ => unreachable!(),
+      let mut end = 0;
+      let tile = re
+        .find_iter(line)
+        .map(|dir| {
+          if dir.start() != end {
+            return Err(ParseError::new(
+              DAY,
+              input,
+              &line[end..dir.start()],
+              "expected one of `e`, `se`, `sw`, `w`, `nw` or `ne`",
+            ));
+          }
+          end = dir.end();
+          Ok(match dir.as_str() {
+            "e" => Direction::East,
+            "se" => Direction::SouthEast,
+            "sw" => Direction::SouthWest,
+            "w" => Direction::West,
+            "nw" => Direction::NorthWest,
+            "ne" => Direction::NorthEast,
+            _ => unreachable!(),
+          })
         })
-        .collect()
+        .collect::<Result<_, _>>()?;
+      if end != line.len() {
+        return Err(ParseError::new(
+          DAY,
+          input,
+          &line[end..],
+          "expected one of `e`, `se`, `sw`, `w`, `nw` or `ne`",
+        ));
+      }
+      Ok(tile)
     })
     .collect()
 }
@@ -71,65 +102,49 @@ fn get_black_tile_set(tiles: &[Tile]) -> HashSet<Pos> {
       }
     }
     let pos = (r, q);
-    if black_tiles.contains(&pos) {
-      black_tiles.remove(&pos);
-    } else {
+    if !black_tiles.remove(&pos) {
       black_tiles.insert(pos);
     }
   }
 
-  return black_tiles;
+  black_tiles
 }
 
 #[aoc(day24, part2)]
 pub fn solve_part2(tiles: &[Tile]) -> usize {
-  let mut black_tiles = get_black_tile_set(tiles);
-  hexa_conway_game_of_life(&mut black_tiles, 100)
+  // black tiles with zero or more than 2 black neighbours and white tiles with exactly 2 flip
+  let mut automaton = Automaton::new(Hex, Rule::new(&[2], &[1, 2]), get_black_tile_set(tiles));
+  for _ in 0..100 {
+    automaton.step();
+  }
+  automaton.alive_count()
 }
 
-fn hexa_conway_game_of_life(black_tiles: &mut HashSet<Pos>, cycles: usize) -> usize {
-  const NEIGHBOUR_DIRECTIONS: [(i32, i32); 6] =
-    [(1, 0), (-1, 0), (0, 1), (0, -1), (1, -1), (-1, 1)];
-  let mut next_black_tiles: HashSet<Pos> = HashSet::new();
-
-  let mut neighbour_count: HashMap<Pos, usize> = HashMap::new();
-
-  for _ in 0..cycles {
-    for (tx, ty) in black_tiles.iter() {
-      for (dx, dy) in &NEIGHBOUR_DIRECTIONS {
-        let n = (tx + dx, ty + dy);
-        neighbour_count
-          .entry(n)
-          .and_modify(|c| *c += 1)
-          .or_insert(1);
-      }
-    }
+pub struct Day24;
 
-    next_black_tiles.clear();
+impl Solution for Day24 {
+  const DAY: u8 = DAY;
 
-    neighbour_count.drain().for_each(|(pos, c)| {
-      if black_tiles.contains(&pos) {
-        if c <= 2 {
-          next_black_tiles.insert(pos);
-        }
-      } else {
-        if c == 2 {
-          next_black_tiles.insert(pos);
-        }
-      }
-    });
+  type Input = Vec<Tile>;
 
-    std::mem::swap(black_tiles, &mut next_black_tiles);
+  fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+    input_generator(input)
   }
 
-  black_tiles.len()
+  fn part1(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part1(input).into()
+  }
+
+  fn part2(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part2(input).into()
+  }
 }
 
 #[cfg(test)]
 mod test {
   use super::{input_generator, solve_part1, solve_part2};
 
-  const TEST_INPUT: &'static str = "sesenwnenenewseeswwswswwnenewsewsw\n\
+  const TEST_INPUT: &str = "sesenwnenenewseeswwswswwnenewsewsw\n\
   neeenesenwnwwswnenewnwwsewnenwseswesw\n\
   seswneswswsenwwnwse\n\
   nwnwneseeswswnenewneswwnewseswneseene\n\
@@ -152,11 +167,11 @@ mod test {
 
   #[test]
   fn test_day24_part1() {
-    assert_eq!(solve_part1(&input_generator(TEST_INPUT)), 10);
+    assert_eq!(solve_part1(&input_generator(TEST_INPUT).unwrap()), 10);
   }
 
   #[test]
   fn test_day24_part2() {
-    assert_eq!(solve_part2(&input_generator(TEST_INPUT)), 2208);
+    assert_eq!(solve_part2(&input_generator(TEST_INPUT).unwrap()), 2208);
   }
 }
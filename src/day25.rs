@@ -1,15 +1,31 @@
+use crate::{
+  answer::Answer, diagnostics::Diagnostics, input::lines, parse_at, solution::Solution, ParseError,
+};
+
+const DAY: u8 = 25;
+
 #[aoc_generator(day25)]
-pub fn input_generator(input: &str) -> (u64, u64) {
-  let mut lines = input.lines().map(|line| line.parse().unwrap());
+pub fn input_generator(input: &str) -> Result<(u64, u64), ParseError> {
+  let mut lines = lines(input);
+  let mut public_key = || {
+    let line = lines.next().ok_or_else(|| {
+      ParseError::new(
+        DAY,
+        input,
+        &input[input.len()..],
+        "expected two public keys",
+      )
+    })?;
+    parse_at(DAY, input, line, "expected a public key")
+  };
 
-  (lines.next().unwrap(), lines.next().unwrap())
+  Ok((public_key()?, public_key()?))
 }
 
 #[aoc(day25, part1)]
 pub fn solve_part1((card_public_key, door_public_key): &(u64, u64)) -> u64 {
   let card_loop_size = reverse_engineer_loop_size(7, *card_public_key);
-  let encryption_key = handshake_operation(*door_public_key, card_loop_size);
-  return encryption_key;
+  handshake_operation(*door_public_key, card_loop_size)
 }
 
 fn reverse_engineer_loop_size(subject_number: u64, public_key: u64) -> u64 {
@@ -29,7 +45,28 @@ fn handshake_operation(subject_number: u64, loop_size: u64) -> u64 {
   for _ in 0..loop_size {
     value = (value * subject_number) % 20201227;
   }
-  return value;
+  value
+}
+
+pub struct Day25;
+
+impl Solution for Day25 {
+  const DAY: u8 = DAY;
+  const PARTS: &'static [u8] = &[1];
+
+  type Input = (u64, u64);
+
+  fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+    input_generator(input)
+  }
+
+  fn part1(&self, input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    solve_part1(input).into()
+  }
+
+  fn part2(&self, _input: &Self::Input, _diagnostics: &dyn Diagnostics) -> Answer {
+    Answer::NoSolution
+  }
 }
 
 #[cfg(test)]
@@ -0,0 +1,104 @@
+use std::{cell::RefCell, fmt};
+
+/// Something noteworthy that happened while solving a puzzle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+  /// Day 8: the program terminates after changing the operation on `line`,
+  /// the instructions are written like in the input, e.g. `jmp +4`.
+  CorruptedInstruction {
+    line: usize,
+    corrupted: String,
+    fixed: String,
+    acc: i32,
+  },
+  /// Day 9: the numbers in `start..end` sum up to `sum`.
+  RangeFound { start: usize, end: usize, sum: u64 },
+  /// Day 17: the number of active cubes before the first cycle.
+  InitialActive { count: usize },
+  /// Day 17: the number of active cubes after `cycle`, counting from 0.
+  CycleFinished { cycle: usize, active: usize },
+  /// Day 20: the number of tiles on each side of the puzzle.
+  PuzzleSideLength { side_length: usize },
+  /// Day 20: tile `id` was attached to `border` of the piece at (`row`, `col`).
+  TileMatched {
+    id: u64,
+    row: usize,
+    col: usize,
+    border: &'static str,
+  },
+  /// Day 20: the number of sea monsters in the picture.
+  MonstersFound { count: usize },
+}
+
+impl fmt::Display for Event {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Event::CorruptedInstruction {
+        line,
+        corrupted,
+        fixed,
+        acc,
+      } => write!(
+        f,
+        "corrupted instruction on line {}: `{}`, set to `{}`, acc is {}",
+        line, corrupted, fixed, acc
+      ),
+      Event::RangeFound { start, end, sum } => {
+        write!(f, "found range {}..{} with sum {}", start, end, sum)
+      }
+      Event::InitialActive { count } => write!(f, "initial active: {}", count),
+      Event::CycleFinished { cycle, active } => write!(f, "cycle {}: {} active", cycle, active),
+      Event::PuzzleSideLength { side_length } => write!(f, "puzzle side length: {}", side_length),
+      Event::TileMatched {
+        id,
+        row,
+        col,
+        border,
+      } => write!(f, "match piece {} at ({} {} {})", id, row, col, border),
+      Event::MonstersFound { count } => write!(f, "{} monsters found", count),
+    }
+  }
+}
+
+/// Receives the events reported by the solvers.
+pub trait Diagnostics {
+  fn report(&self, event: Event);
+}
+
+/// Ignores all events.
+pub struct Silent;
+
+impl Diagnostics for Silent {
+  fn report(&self, _event: Event) {}
+}
+
+/// Prints every event to stderr.
+pub struct Logger;
+
+impl Diagnostics for Logger {
+  fn report(&self, event: Event) {
+    eprintln!("{}", event);
+  }
+}
+
+/// Keeps all events in the order they were reported.
+#[derive(Default)]
+pub struct Collector {
+  events: RefCell<Vec<Event>>,
+}
+
+impl Collector {
+  pub fn new() -> Collector {
+    Collector::default()
+  }
+
+  pub fn into_events(self) -> Vec<Event> {
+    self.events.into_inner()
+  }
+}
+
+impl Diagnostics for Collector {
+  fn report(&self, event: Event) {
+    self.events.borrow_mut().push(event);
+  }
+}
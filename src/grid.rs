@@ -0,0 +1,334 @@
+use crate::{input::lines, ParseError};
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+/// The directions to the 4 orthogonal neighbours as `(dx, dy)`, clockwise starting upwards.
+pub const DIRECTIONS_4: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+/// The directions to the 8 neighbours including diagonals, clockwise starting upwards.
+pub const DIRECTIONS_8: [(isize, isize); 8] = [
+  (0, -1),
+  (1, -1),
+  (1, 0),
+  (1, 1),
+  (0, 1),
+  (-1, 1),
+  (-1, 0),
+  (-1, -1),
+];
+
+/// A rectangular grid of cells stored row by row.
+/// Positions are `(x, y)` pairs, `x` is the column and `y` the row, both counted from the top left.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+  width: usize,
+  height: usize,
+  cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+  /// Creates a grid from its cells row by row, panics if there aren't `width * height` of them.
+  pub fn new(width: usize, height: usize, cells: Vec<T>) -> Grid<T> {
+    assert_eq!(cells.len(), width * height, "wrong number of cells");
+    Grid {
+      width,
+      height,
+      cells,
+    }
+  }
+
+  pub fn from_fn<F: FnMut(usize, usize) -> T>(width: usize, height: usize, mut f: F) -> Grid<T> {
+    let cells = (0..height)
+      .flat_map(|y| (0..width).map(move |x| (x, y)))
+      .map(|(x, y)| f(x, y))
+      .collect();
+    Grid::new(width, height, cells)
+  }
+
+  /// Parses the lines of `text` into a grid, converting each character with `cell`.
+  /// `text` has to be a slice of the puzzle `input` of `day`, and `expected` describes the
+  /// characters that `cell` accepts.
+  pub fn parse<F: FnMut(char) -> Option<T>>(
+    day: u8,
+    input: &str,
+    text: &str,
+    expected: &str,
+    mut cell: F,
+  ) -> Result<Grid<T>, ParseError> {
+    let mut width = None;
+    let mut height = 0;
+    let mut cells = Vec::new();
+
+    for line in lines(text) {
+      let row_start = cells.len();
+      for (i, c) in line.char_indices() {
+        let value = cell(c)
+          .ok_or_else(|| ParseError::new(day, input, &line[i..i + c.len_utf8()], expected))?;
+        cells.push(value);
+      }
+      let row_width = cells.len() - row_start;
+      if *width.get_or_insert(row_width) != row_width {
+        return Err(ParseError::new(
+          day,
+          input,
+          line,
+          "all rows of the grid must have the same width",
+        ));
+      }
+      height += 1;
+    }
+
+    match width {
+      Some(width) if width > 0 => Ok(Grid::new(width, height, cells)),
+      _ => Err(ParseError::new(day, input, text, "the grid is empty")),
+    }
+  }
+
+  pub fn width(&self) -> usize {
+    self.width
+  }
+
+  pub fn height(&self) -> usize {
+    self.height
+  }
+
+  pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+    if x < self.width && y < self.height {
+      Some(&self.cells[y * self.width + x])
+    } else {
+      None
+    }
+  }
+
+  pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+    if x < self.width && y < self.height {
+      Some(&mut self.cells[y * self.width + x])
+    } else {
+      None
+    }
+  }
+
+  /// Returns the cell at `(x, y)` of the grid repeated infinitely in all directions.
+  pub fn get_wrapping(&self, x: isize, y: isize) -> &T {
+    let x = x.rem_euclid(self.width as isize) as usize;
+    let y = y.rem_euclid(self.height as isize) as usize;
+    &self[(x, y)]
+  }
+
+  /// Returns the cell at `(x, y)`, or the closest cell on the border if it's outside of the grid.
+  pub fn get_clamped(&self, x: isize, y: isize) -> &T {
+    let x = x.clamp(0, self.width as isize - 1) as usize;
+    let y = y.clamp(0, self.height as isize - 1) as usize;
+    &self[(x, y)]
+  }
+
+  /// Returns the position one step from `(x, y)` in `direction`, if it's inside of the grid.
+  pub fn step(&self, (x, y): (usize, usize), direction: (isize, isize)) -> Option<(usize, usize)> {
+    let x = x.checked_add_signed(direction.0)?;
+    let y = y.checked_add_signed(direction.1)?;
+    if x < self.width && y < self.height {
+      Some((x, y))
+    } else {
+      None
+    }
+  }
+
+  /// Returns the positions inside of the grid in all `directions` from `(x, y)`.
+  pub fn neighbours(
+    &self,
+    (x, y): (usize, usize),
+    directions: &[(isize, isize)],
+  ) -> Vec<(usize, usize)> {
+    directions
+      .iter()
+      .filter_map(|&direction| self.step((x, y), direction))
+      .collect()
+  }
+
+  pub fn neighbours4(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+    self.neighbours((x, y), &DIRECTIONS_4)
+  }
+
+  pub fn neighbours8(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+    self.neighbours((x, y), &DIRECTIONS_8)
+  }
+
+  /// Returns the positions from `(x, y)` in `direction` up to the border of the grid,
+  /// not including `(x, y)` itself.
+  pub fn ray(
+    &self,
+    x: usize,
+    y: usize,
+    direction: (isize, isize),
+  ) -> impl Iterator<Item = (usize, usize)> {
+    let (width, height) = (self.width, self.height);
+    let mut position = (x, y);
+    std::iter::from_fn(move || {
+      let x = position.0.checked_add_signed(direction.0)?;
+      let y = position.1.checked_add_signed(direction.1)?;
+      if x < width && y < height {
+        position = (x, y);
+        Some(position)
+      } else {
+        None
+      }
+    })
+  }
+
+  /// Returns all positions row by row.
+  pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+    let width = self.width;
+    (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+  }
+
+  /// Returns all cells row by row.
+  pub fn iter(&self) -> std::slice::Iter<'_, T> {
+    self.cells.iter()
+  }
+
+  pub fn row(&self, y: usize) -> &[T] {
+    &self.cells[y * self.width..(y + 1) * self.width]
+  }
+
+  pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+    self.cells[x..].iter().step_by(self.width)
+  }
+
+  pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> Grid<U> {
+    Grid::new(self.width, self.height, self.cells.iter().map(f).collect())
+  }
+
+  /// Renders the grid as text, one line per row.
+  pub fn render<F: FnMut(&T) -> char>(&self, mut f: F) -> String {
+    (0..self.height)
+      .map(|y| self.row(y).iter().map(&mut f).collect::<String>())
+      .collect::<Vec<_>>()
+      .join("\n")
+  }
+}
+
+impl<T: Clone> Grid<T> {
+  pub fn filled(width: usize, height: usize, value: T) -> Grid<T> {
+    Grid::new(width, height, vec![value; width * height])
+  }
+
+  /// Rotates the grid clockwise by 90 degrees.
+  pub fn rotate_cw(&mut self) {
+    let height = self.height;
+    *self = Grid::from_fn(self.height, self.width, |x, y| {
+      self[(y, height - 1 - x)].clone()
+    });
+  }
+
+  /// Mirrors the grid at its vertical axis, so the left column becomes the right one.
+  pub fn flip_horizontal(&mut self) {
+    let width = self.width;
+    for row in self.cells.chunks_mut(width) {
+      row.reverse();
+    }
+  }
+
+  /// Mirrors the grid at its horizontal axis, so the top row becomes the bottom one.
+  pub fn flip_vertical(&mut self) {
+    let (width, height) = (self.width, self.height);
+    *self = Grid::from_fn(width, height, |x, y| self[(x, height - 1 - y)].clone());
+  }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+  type Output = T;
+
+  fn index(&self, (x, y): (usize, usize)) -> &T {
+    self.get(x, y).expect("position outside of the grid")
+  }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+  fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+    self.get_mut(x, y).expect("position outside of the grid")
+  }
+}
+
+impl fmt::Display for Grid<char> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(&self.render(|&c| c))
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::{Grid, DIRECTIONS_8};
+
+  fn parse(input: &str) -> Grid<char> {
+    Grid::parse(0, input, input, "expected `#` or `.`", |c| {
+      if c == '#' || c == '.' {
+        Some(c)
+      } else {
+        None
+      }
+    })
+    .unwrap()
+  }
+
+  #[test]
+  fn test_grid_parse() {
+    let grid = parse("#..\n.#.\r\n");
+    assert_eq!((grid.width(), grid.height()), (3, 2));
+    assert_eq!(grid[(1, 1)], '#');
+    assert_eq!(grid.get(3, 0), None);
+    assert_eq!(grid.to_string(), "#..\n.#.");
+
+    let error = Grid::parse(0, "#.\n#x", "#.\n#x", "expected `#` or `.`", |c| {
+      if c == '#' || c == '.' {
+        Some(c)
+      } else {
+        None
+      }
+    })
+    .unwrap_err();
+    assert_eq!((error.line, error.column), (2, 2));
+    let error = Grid::parse(0, "#.\n#", "#.\n#", "", Some).unwrap_err();
+    assert_eq!((error.line, error.column), (2, 1));
+    assert!(Grid::parse(0, "", "", "", Some).is_err());
+  }
+
+  #[test]
+  fn test_grid_access() {
+    let grid = parse("#..\n.#.\n..#\n...");
+    assert_eq!(*grid.get_wrapping(4, -3), '#');
+    assert_eq!(*grid.get_clamped(5, -1), '.');
+    assert_eq!(*grid.get_clamped(-2, -1), '#');
+    assert_eq!(grid.row(2), &['.', '.', '#']);
+    assert_eq!(grid.column(1).collect::<String>(), ".#..");
+    assert_eq!(grid.neighbours4(0, 0), vec![(1, 0), (0, 1)]);
+    assert_eq!(grid.neighbours8(2, 3), vec![(2, 2), (1, 3), (1, 2)]);
+    assert_eq!(
+      grid
+        .neighbours(grid.positions().nth(4).unwrap(), &DIRECTIONS_8)
+        .len(),
+      8
+    );
+    assert_eq!(
+      grid.ray(0, 0, (1, 1)).collect::<Vec<_>>(),
+      vec![(1, 1), (2, 2)]
+    );
+    assert_eq!(grid.ray(0, 0, (-1, 0)).count(), 0);
+  }
+
+  #[test]
+  fn test_grid_transform() {
+    let mut grid = parse("##.\n...");
+    grid.rotate_cw();
+    assert_eq!(grid.to_string(), ".#\n.#\n..");
+    grid.flip_horizontal();
+    assert_eq!(grid.to_string(), "#.\n#.\n..");
+    grid.flip_vertical();
+    assert_eq!(grid.to_string(), "..\n#.\n#.");
+    assert_eq!(
+      grid
+        .map(|&c| c == '#')
+        .render(|&b| if b { 'X' } else { 'O' }),
+      "OO\nXO\nXO"
+    );
+  }
+}
@@ -0,0 +1,78 @@
+//! Splitting of puzzle inputs, shared by all input generators.
+//!
+//! Both functions accept `\n` as well as `\r\n` line endings and ignore trailing whitespace, so an
+//! input parses the same no matter which editor saved it. They return slices of the input, which
+//! can be passed on to `ParseError::new` to locate an error.
+
+/// Returns the lines of `input` without line endings and trailing whitespace.
+/// Blank lines at the end of the input are skipped.
+pub fn lines(input: &str) -> impl Iterator<Item = &str> {
+  let input = input.trim_end();
+  let lines = if input.is_empty() {
+    None
+  } else {
+    Some(input.split('\n').map(str::trim_end))
+  };
+  lines.into_iter().flatten()
+}
+
+/// Returns the blocks of `input`, which are separated by one or more blank lines.
+/// A block ends with its last non-blank character.
+pub fn blocks(input: &str) -> impl Iterator<Item = &str> {
+  let mut blocks = Vec::new();
+  let mut block: Option<(usize, usize)> = None;
+  let mut offset = 0;
+
+  for line in input.split_inclusive('\n') {
+    let content = line.trim_end();
+    if content.is_empty() {
+      if let Some((start, end)) = block.take() {
+        blocks.push(&input[start..end]);
+      }
+    } else {
+      let start = block.map_or(offset, |(start, _)| start);
+      block = Some((start, offset + content.len()));
+    }
+    offset += line.len();
+  }
+
+  if let Some((start, end)) = block {
+    blocks.push(&input[start..end]);
+  }
+  blocks.into_iter()
+}
+
+#[cfg(test)]
+mod test {
+  use super::{blocks, lines};
+
+  #[test]
+  fn test_lines() {
+    let expected = vec!["1721", "979", "", "366"];
+    assert_eq!(lines("1721\n979\n\n366").collect::<Vec<_>>(), expected);
+    assert_eq!(
+      lines("1721\r\n979 \r\n\r\n366\r\n\r\n").collect::<Vec<_>>(),
+      expected
+    );
+    assert_eq!(lines("\n \n").count(), 0);
+  }
+
+  #[test]
+  fn test_blocks() {
+    let expected = vec!["abc", "a\nb\nc", "ab\nac"];
+    assert_eq!(
+      blocks("abc\n\na\nb\nc\n\nab\nac").collect::<Vec<_>>(),
+      expected
+    );
+    assert_eq!(
+      blocks("\nabc\n \n\n\na\nb\nc\n\nab\nac\n\n").collect::<Vec<_>>(),
+      expected
+    );
+    assert_eq!(
+      blocks("abc\r\n\r\na\r\nb\r\nc\r\n\r\nab\r\nac\r\n")
+        .flat_map(lines)
+        .collect::<Vec<_>>(),
+      vec!["abc", "a", "b", "c", "ab", "ac"]
+    );
+  }
+}
@@ -1,4 +1,4 @@
-use std::{error::Error, fmt};
+use std::{error::Error, fmt, str::FromStr};
 
 extern crate aoc_runner;
 
@@ -8,8 +8,14 @@ extern crate aoc_runner_derive;
 #[macro_use]
 extern crate lazy_static;
 extern crate itertools;
+extern crate plotters;
 extern crate regex;
+extern crate serde;
+extern crate serde_json;
+extern crate unicode_segmentation;
 
+pub mod answer;
+pub mod automaton;
 pub mod day01;
 pub mod day02;
 pub mod day03;
@@ -35,6 +41,46 @@ pub mod day22;
 pub mod day23;
 pub mod day24;
 pub mod day25;
+pub mod diagnostics;
+pub mod grid;
+pub mod input;
+pub mod solution;
+
+use solution::DynSolution;
+
+/// The solutions of all days, ordered by day.
+pub static SOLUTIONS: [&dyn DynSolution; 25] = [
+  &day01::Day01,
+  &day02::Day02,
+  &day03::Day03,
+  &day04::Day04,
+  &day05::Day05,
+  &day06::Day06,
+  &day07::Day07,
+  &day08::Day08,
+  &day09::Day09,
+  &day10::Day10,
+  &day11::Day11,
+  &day12::Day12,
+  &day13::Day13,
+  &day14::Day14,
+  &day15::Day15,
+  &day16::Day16,
+  &day17::Day17,
+  &day18::Day18,
+  &day19::Day19,
+  &day20::Day20,
+  &day21::Day21,
+  &day22::Day22,
+  &day23::Day23,
+  &day24::Day24,
+  &day25::Day25,
+];
+
+/// Returns the solution of `day`, counting from 1.
+pub fn solution(day: u8) -> Option<&'static dyn DynSolution> {
+  SOLUTIONS.get((day as usize).wrapping_sub(1)).copied()
+}
 
 #[derive(Debug)]
 pub struct NoSolutionErr {}
@@ -45,4 +91,66 @@ impl std::fmt::Display for NoSolutionErr {
 }
 impl Error for NoSolutionErr {}
 
+/// An error of an input generator, pointing to the place in the puzzle input where parsing failed.
+/// `line` and `column` are 1-based, the column is counted in characters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+  pub day: u8,
+  pub line: usize,
+  pub column: usize,
+  pub text: String,
+  pub reason: String,
+}
+
+impl ParseError {
+  /// Creates an error for `fragment`, which has to be a slice of `input`.
+  /// Line and column are computed from the position of the fragment inside the input.
+  pub fn new(day: u8, input: &str, fragment: &str, reason: &str) -> ParseError {
+    let start = input.as_ptr() as usize;
+    let offset = (fragment.as_ptr() as usize)
+      .saturating_sub(start)
+      .min(input.len());
+    let before = &input[..floor_char_boundary(input, offset)];
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+
+    ParseError {
+      day,
+      line: before.matches('\n').count() + 1,
+      column: before[line_start..].chars().count() + 1,
+      text: fragment.to_string(),
+      reason: reason.to_string(),
+    }
+  }
+}
+
+fn floor_char_boundary(s: &str, mut index: usize) -> usize {
+  while !s.is_char_boundary(index) {
+    index -= 1;
+  }
+  index
+}
+
+impl fmt::Display for ParseError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "day {}, line {}, column {}: {} (found `{}`)",
+      self.day, self.line, self.column, self.reason, self.text
+    )
+  }
+}
+impl Error for ParseError {}
+
+/// Parses `fragment` of `input` into a `T`, reporting `reason` at the fragment's position on failure.
+pub fn parse_at<T: FromStr>(
+  day: u8,
+  input: &str,
+  fragment: &str,
+  reason: &str,
+) -> Result<T, ParseError> {
+  fragment
+    .parse()
+    .map_err(|_| ParseError::new(day, input, fragment, reason))
+}
+
 aoc_lib! { year = 2020 }
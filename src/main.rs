@@ -1,12 +1,30 @@
 extern crate advent_of_code_2020;
 extern crate plotters;
 
+use advent_of_code_2020::day03::{self, Sampling, Slope};
 use advent_of_code_2020::day17::{conway_nd, input_generator};
+use advent_of_code_2020::diagnostics::Silent;
 use plotters::coord::Shift;
 use plotters::prelude::*;
 use plotters::style::text_anchor::*;
+use std::path::Path;
 
+/// Draws the visualization named by the first argument, `day17` (the default) or `day3`.
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+  match std::env::args().nth(1).as_deref() {
+    None | Some("day17") => draw_active_cubes(),
+    Some("day3") => draw_toboggan_routes(),
+    Some(other) => Err(
+      format!(
+        "unknown visualization `{}`, expected `day3` or `day17`",
+        other
+      )
+      .into(),
+    ),
+  }
+}
+
+fn draw_active_cubes() -> Result<(), Box<dyn std::error::Error>> {
   let root_drawing_area =
     BitMapBackend::new("visualization/day17_plot.png", (600, 800)).into_drawing_area();
   root_drawing_area.fill(&WHITE)?;
@@ -34,11 +52,41 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
   Ok(())
 }
 
-fn build_chart<'a>(
-  area: &'a DrawingArea<BitMapBackend, Shift>,
+fn draw_toboggan_routes() -> Result<(), Box<dyn std::error::Error>> {
+  const INPUT: &str = "..##.......\n\
+  #...#...#..\n\
+  .#....#..#.\n\
+  ..#.#...#.#\n\
+  .#...##..#.\n\
+  ..#.##.....\n\
+  .#.#.#....#\n\
+  .#........#\n\
+  #.##...#...\n\
+  #...##....#\n\
+  .#..#...#.#";
+
+  let slopes = [
+    Slope::new(1, 1),
+    Slope::new(3, 1),
+    Slope::new(5, 1),
+    Slope::new(7, 1),
+    Slope::new(1, 2),
+  ];
+  let tree_map = day03::input_generator(INPUT)?;
+  println!("{}", tree_map.route_map(&slopes, Sampling::Steps));
+  tree_map.draw_routes(
+    &slopes,
+    Sampling::Steps,
+    Path::new("visualization/day03_routes.png"),
+    12,
+  )
+}
+
+fn build_chart(
+  area: &DrawingArea<BitMapBackend, Shift>,
   n: usize,
 ) -> Result<(), Box<dyn std::error::Error>> {
-  const INPUT: &'static str = ".#.#.#..\n\
+  const INPUT: &str = ".#.#.#..\n\
 	..#....#\n\
 	#####..#\n\
 	#####..#\n\
@@ -48,10 +96,8 @@ fn build_chart<'a>(
 	#.#.####";
 
   let cycles = if n < 7 { 6 } else { 4 };
-  println!("{} dimensions", n);
-  println!("-------------");
-  let active_count = conway_nd(n, &input_generator(INPUT, n), cycles);
-  println!();
+  let active_count = conway_nd(n, &input_generator(INPUT, n)?, cycles, &Silent);
+  println!("{} dimensions: {:?}", n, active_count);
   let mut max = *active_count.iter().max().unwrap();
   max += (max as f64 * 0.05) as usize;
   let mut chart = ChartBuilder::on(area)
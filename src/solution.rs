@@ -0,0 +1,108 @@
+use crate::{
+  answer::Answer,
+  diagnostics::{Diagnostics, Silent},
+  ParseError,
+};
+use std::any::Any;
+use std::error::Error;
+
+/// The solution of one day: an input generator and the solvers of both parts.
+pub trait Solution {
+  const DAY: u8;
+  /// The parts that have a puzzle, the last day only has one.
+  const PARTS: &'static [u8] = &[1, 2];
+
+  type Input;
+
+  fn parse(&self, input: &str) -> Result<Self::Input, ParseError>;
+  fn part1(&self, input: &Self::Input, diagnostics: &dyn Diagnostics) -> Answer;
+  fn part2(&self, input: &Self::Input, diagnostics: &dyn Diagnostics) -> Answer;
+}
+
+/// An object safe version of `Solution`, implemented for every solution.
+/// It allows to keep the solutions of all days in one list, see `SOLUTIONS`.
+pub trait DynSolution: Sync {
+  fn day(&self) -> u8;
+  fn parts(&self) -> &'static [u8];
+  fn parse(&self, input: &str) -> Result<Box<dyn Any>, ParseError>;
+  /// Solves `part` for an input returned by `parse`, reporting to `diagnostics`.
+  fn solve(
+    &self,
+    part: u8,
+    input: &dyn Any,
+    diagnostics: &dyn Diagnostics,
+  ) -> Result<Answer, Box<dyn Error>>;
+
+  /// Parses the input and solves `part` without diagnostics.
+  fn run(&self, part: u8, input: &str) -> Result<Answer, Box<dyn Error>> {
+    let input = self.parse(input)?;
+    self.solve(part, &*input, &Silent)
+  }
+}
+
+impl<S> DynSolution for S
+where
+  S: Solution + Sync,
+  S::Input: 'static,
+{
+  fn day(&self) -> u8 {
+    S::DAY
+  }
+
+  fn parts(&self) -> &'static [u8] {
+    S::PARTS
+  }
+
+  fn parse(&self, input: &str) -> Result<Box<dyn Any>, ParseError> {
+    Ok(Box::new(Solution::parse(self, input)?))
+  }
+
+  fn solve(
+    &self,
+    part: u8,
+    input: &dyn Any,
+    diagnostics: &dyn Diagnostics,
+  ) -> Result<Answer, Box<dyn Error>> {
+    let input = input
+      .downcast_ref::<S::Input>()
+      .ok_or("the input wasn't parsed by this solution")?;
+    match part {
+      1 if S::PARTS.contains(&1) => Ok(self.part1(input, diagnostics)),
+      2 if S::PARTS.contains(&2) => Ok(self.part2(input, diagnostics)),
+      _ => Err(format!("day {} has no part {}", S::DAY, part).into()),
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::{answer::Answer, solution, SOLUTIONS};
+
+  #[test]
+  fn test_solutions_are_ordered_by_day() {
+    for (i, solution) in SOLUTIONS.iter().enumerate() {
+      assert_eq!(solution.day() as usize, i + 1);
+    }
+  }
+
+  #[test]
+  fn test_run_dyn_solution() {
+    let day1 = solution(1).unwrap();
+    let input = "1721\n979\n366\n299\n675\n1456";
+    assert_eq!(day1.run(1, input).unwrap(), Answer::from(514579));
+    assert_eq!(day1.run(2, input).unwrap(), Answer::from(241861950));
+    assert_eq!(day1.run(1, "1721").unwrap(), Answer::NoSolution);
+    assert!(solution(25).unwrap().run(2, "5764801\n17807724").is_err());
+    assert!(solution(26).is_none());
+  }
+
+  #[test]
+  fn test_short_inputs_are_rejected() {
+    for day in [8, 10, 20] {
+      let error = solution(day).unwrap().parse("\n").unwrap_err();
+      assert_eq!(error.day, day);
+    }
+    let two_tiles = "Tile 1:\n#.\n.#\n\nTile 2:\n##\n..";
+    assert!(solution(20).unwrap().parse(two_tiles).is_err());
+  }
+}
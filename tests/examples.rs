@@ -0,0 +1,150 @@
+//! Runs every example in `input/2020/examples` through the generator and solvers of its day.
+//!
+//! An example file is named `day<day>[-<label>].txt` and starts with the expected answers, one
+//! `part<part>: <answer>` line per part, followed by a `---` line and the puzzle input. Examples
+//! with a `slow: true` line only run in release builds, i.e. with `cargo test --release`.
+
+extern crate advent_of_code_2020;
+
+use advent_of_code_2020::answer::Answer;
+use advent_of_code_2020::diagnostics::Silent;
+use advent_of_code_2020::{solution, SOLUTIONS};
+use std::collections::HashSet;
+use std::fs;
+
+const EXAMPLES_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/input/2020/examples");
+
+struct Example {
+  name: String,
+  day: u8,
+  expected: Vec<(u8, Answer)>,
+  slow: bool,
+  input: String,
+}
+
+fn parse_example(name: &str, content: &str) -> Result<Example, String> {
+  let day = name
+    .strip_prefix("day")
+    .and_then(|rest| rest.split(['-', '.']).next())
+    .and_then(|day| day.parse().ok())
+    .ok_or("the file name doesn't start with day<day>")?;
+
+  let (header, input) = content
+    .split_once("---\n")
+    .ok_or("missing `---` line after the answers")?;
+
+  let mut expected = Vec::new();
+  let mut slow = false;
+  for line in header.lines() {
+    let (key, value) = line
+      .split_once(": ")
+      .ok_or_else(|| format!("invalid header line `{}`", line))?;
+    match key {
+      "slow" => {
+        slow = value
+          .parse()
+          .map_err(|_| format!("invalid flag in `{}`", line))?
+      }
+      _ => {
+        let part = key
+          .strip_prefix("part")
+          .and_then(|part| part.parse().ok())
+          .ok_or_else(|| format!("invalid part in `{}`", line))?;
+        expected.push((part, value.parse().unwrap()));
+      }
+    }
+  }
+
+  if expected.is_empty() {
+    return Err(String::from("no expected answers"));
+  }
+
+  Ok(Example {
+    name: name.to_string(),
+    day,
+    expected,
+    slow,
+    // cargo-aoc passes the input without the trailing newline
+    input: input.trim_end_matches('\n').to_string(),
+  })
+}
+
+fn load_examples() -> Vec<Example> {
+  let mut examples = fs::read_dir(EXAMPLES_DIR)
+    .unwrap()
+    .map(|entry| {
+      let path = entry.unwrap().path();
+      let name = path.file_name().unwrap().to_string_lossy().into_owned();
+      let content = fs::read_to_string(&path).unwrap();
+      parse_example(&name, &content).unwrap_or_else(|e| panic!("{}: {}", name, e))
+    })
+    .collect::<Vec<_>>();
+  examples.sort_by(|a, b| a.name.cmp(&b.name));
+  examples
+}
+
+/// Solves all examples with their input changed by `edit_input`.
+fn check_examples(edit_input: fn(&str) -> String) {
+  let mut failures = Vec::new();
+
+  for example in load_examples() {
+    if example.slow && cfg!(debug_assertions) {
+      continue;
+    }
+    let solution = solution(example.day).unwrap();
+    let input = match solution.parse(&edit_input(&example.input)) {
+      Ok(input) => input,
+      Err(e) => {
+        failures.push(format!("{}: {}", example.name, e));
+        continue;
+      }
+    };
+
+    for (part, expected) in &example.expected {
+      match solution.solve(*part, &*input, &Silent) {
+        Ok(ref answer) if answer.matches(expected) => (),
+        Ok(answer) => failures.push(format!(
+          "{} part {}: expected {}, got {}",
+          example.name, part, expected, answer
+        )),
+        Err(e) => failures.push(format!("{} part {}: {}", example.name, part, e)),
+      }
+    }
+  }
+
+  assert!(failures.is_empty(), "\n{}\n", failures.join("\n"));
+}
+
+#[test]
+fn test_examples() {
+  check_examples(str::to_string);
+}
+
+#[test]
+fn test_examples_saved_on_windows() {
+  check_examples(|input| input.replace('\n', " \r\n") + "\r\n\r\n");
+}
+
+#[test]
+fn test_examples_cover_every_part() {
+  let covered = load_examples()
+    .iter()
+    .flat_map(|example| {
+      example
+        .expected
+        .iter()
+        .map(move |(part, _)| (example.day, *part))
+    })
+    .collect::<HashSet<_>>();
+
+  for solution in SOLUTIONS.iter() {
+    for &part in solution.parts() {
+      assert!(
+        covered.contains(&(solution.day(), part)),
+        "day {} part {} has no example",
+        solution.day(),
+        part
+      );
+    }
+  }
+}